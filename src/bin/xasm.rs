@@ -0,0 +1,99 @@
+extern crate xassembler;
+use xassembler::{format, Printer};
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "usage: xasm fmt [--check] [--width <columns>] [--indent <spaces> | --indent tab] [<file>...]
+
+Formats each file in place, or standard input to standard output when no files are given.
+With --check, nothing is written; the files that aren't formatted are listed instead,
+and the exit status is 1 if there are any.";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() != Some("fmt") {
+        usage()
+    }
+
+    let mut check = false;
+    let mut printer = Printer::default();
+    let mut files = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--width" => printer.width = args.next().and_then(|width| width.parse().ok()).unwrap_or_else(|| usage()),
+            "--indent" => {
+                printer.indent = match args.next().as_deref() {
+                    Some("tab") => "\t".to_string(),
+                    Some(spaces) => " ".repeat(spaces.parse().unwrap_or_else(|_| usage())),
+                    None => usage(),
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => usage(),
+            _ => files.push(arg),
+        }
+    }
+
+    process::exit(if files.is_empty() { stdin(check, &printer) } else { paths(check, &printer, &files) })
+}
+
+fn stdin(check: bool, printer: &Printer) -> i32 {
+    let mut source = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut source) {
+        eprintln!("error: {}", e);
+        return 1;
+    }
+    match format(&source, printer) {
+        Ok(formatted) if check && formatted != source => {
+            println!("<stdin>");
+            1
+        }
+        Ok(_) if check => 0,
+        Ok(formatted) => {
+            print!("{}", formatted);
+            0
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn paths(check: bool, printer: &Printer, files: &[String]) -> i32 {
+    let mut status = 0;
+    for file in files {
+        let result = fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|source| format(&source, printer).map(|formatted| (source, formatted)));
+        match result {
+            Ok((source, formatted)) if formatted != source => {
+                if check {
+                    println!("{}", file);
+                    status = 1;
+                } else if let Err(e) = fs::write(file, formatted) {
+                    eprintln!("error: {}: {}", file, e);
+                    status = 1;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("error: {}: {}", file, e);
+                status = 1;
+            }
+        }
+    }
+    status
+}
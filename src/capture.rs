@@ -0,0 +1,285 @@
+use crate::{module_binding, module_global, Arm, Destructure, EnumDef, Expr, FnCall, Function, FunctionDef, Identifier, Name, Parameter, Pattern, Slice, StructDef, Suite, Value};
+
+use alloc::collections::BTreeSet;
+use alloc::string::ToString;
+
+
+/// The variables a function refers to without binding them itself, including
+/// those its nested functions refer to. These are captured by reference from
+/// the scope the function is defined in. A name the function assigns to is
+/// its own, and isn't captured, unless it is declared `nonlocal`.
+pub fn free_variables(function: &Function) -> BTreeSet<Identifier> {
+    let Function(parameters, body, _, _) = function;
+    let mut locals = BTreeSet::new();
+    let mut used = BTreeSet::new();
+    for parameter in parameters {
+        match parameter.untyped() {
+            Parameter::Pattern(pattern) => locals.extend(pattern.names()),
+            Parameter::Default(_, value) => value_uses(value, &mut used),
+            _ => {}
+        }
+        locals.extend(parameter.name().cloned());
+    }
+
+    suite_locals(body, &mut locals);
+    suite_uses(body, &mut used);
+
+    // nonlocal names are captured even though the function assigns them,
+    // while global names are looked up in the global scope instead
+    let (mut globals, mut nonlocals) = (BTreeSet::new(), BTreeSet::new());
+    declarations(body, &mut globals, &mut nonlocals);
+    used.extend(nonlocals.iter().cloned());
+    used.retain(|name| !globals.contains(name) && (nonlocals.contains(name) || !locals.contains(name)));
+    used
+}
+
+
+/// Collects the names a suite declares global or nonlocal, excluding
+/// those declared by its nested functions
+pub(crate) fn declarations(suite: &Suite, globals: &mut BTreeSet<Identifier>, nonlocals: &mut BTreeSet<Identifier>) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        match expr {
+            Expr::Global(names) => globals.extend(names.iter().cloned()),
+            Expr::Nonlocal(names) => nonlocals.extend(names.iter().cloned()),
+            Expr::WhileLoop(_, body) | Expr::With(_, _, body) => declarations(body, globals, nonlocals),
+            Expr::IfThenElse(_, then_body, else_body) => {
+                declarations(then_body, globals, nonlocals);
+                declarations(else_body, globals, nonlocals);
+            }
+            Expr::TryCatch(body, handler, finally) => {
+                declarations(body, globals, nonlocals);
+                if let Some((_, handler)) = handler {
+                    declarations(handler, globals, nonlocals);
+                }
+                declarations(finally, globals, nonlocals);
+            }
+            Expr::Match(_, arms) => {
+                for Arm(_, body) in arms {
+                    declarations(body, globals, nonlocals);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+
+/// Collects the names a suite binds, including those bound inside its loops and branches
+fn suite_locals(suite: &Suite, locals: &mut BTreeSet<Identifier>) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        match expr {
+            Expr::Assignment(Name::Name(name), _)
+            | Expr::Declaration(name, _, _)
+            | Expr::FunctionDef(FunctionDef(Name::Name(name), _, _))
+            | Expr::StructDef(StructDef(Name::Name(name), _, _))
+            | Expr::EnumDef(EnumDef(Name::Name(name), _)) => {
+                locals.insert(name.clone());
+            }
+            Expr::Import(path) => {
+                locals.insert(module_binding(path));
+            }
+            Expr::FromImport(_, names) => locals.extend(names.iter().cloned()),
+            Expr::Destructure(targets, _) => locals.extend(targets.iter().flat_map(Destructure::names)),
+            Expr::WhileLoop(_, body) => suite_locals(body, locals),
+            Expr::With(_, name, body) => {
+                locals.insert(name.clone());
+                suite_locals(body, locals);
+            }
+            Expr::IfThenElse(_, then_body, else_body) => {
+                suite_locals(then_body, locals);
+                suite_locals(else_body, locals);
+            }
+            Expr::TryCatch(body, handler, finally) => {
+                suite_locals(body, locals);
+                if let Some((name, handler)) = handler {
+                    locals.insert(name.clone());
+                    suite_locals(handler, locals);
+                }
+                suite_locals(finally, locals);
+            }
+            Expr::Match(_, arms) => {
+                for Arm(patterns, body) in arms {
+                    for pattern in patterns {
+                        pattern_locals(pattern, locals);
+                    }
+                    suite_locals(body, locals);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn pattern_locals(pattern: &Pattern, locals: &mut BTreeSet<Identifier>) {
+    match pattern {
+        Pattern::Binding(name) => {
+            locals.insert(name.clone());
+        }
+        Pattern::List(patterns) | Pattern::Variant(_, patterns) => {
+            for pattern in patterns {
+                pattern_locals(pattern, locals);
+            }
+        }
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::Value(_) => {}
+    }
+}
+
+
+/// Collects the names a suite refers to
+fn suite_uses(suite: &Suite, used: &mut BTreeSet<Identifier>) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        match expr {
+            Expr::Assignment(name, value) => {
+                target_uses(name, used);
+                value_uses(value, used);
+            }
+            Expr::Declaration(_, _, value)
+            | Expr::Throw(value)
+            | Expr::Yield(value)
+            | Expr::Defer(value)
+            | Expr::Value(value) => value_uses(value, used),
+            Expr::Assert(condition, message) => {
+                value_uses(condition, used);
+                if let Some(message) = message {
+                    value_uses(message, used);
+                }
+            }
+            Expr::WhileLoop(condition, body) | Expr::With(condition, _, body) => {
+                value_uses(condition, used);
+                suite_uses(body, used);
+            }
+            Expr::IfThenElse(condition, then_body, else_body) => {
+                value_uses(condition, used);
+                suite_uses(then_body, used);
+                suite_uses(else_body, used);
+            }
+            Expr::FunctionDef(FunctionDef(name, function, _)) => {
+                target_uses(name, used);
+                used.extend(free_variables(function));
+            }
+            // methods are defined inside a constructor, which binds `self`
+            Expr::StructDef(StructDef(name, methods, _)) => {
+                target_uses(name, used);
+                let this = Identifier("self".to_string());
+                for FunctionDef(_, function, _) in methods {
+                    used.extend(free_variables(function).into_iter().filter(|name| *name != this));
+                }
+            }
+            Expr::EnumDef(EnumDef(name, _)) => target_uses(name, used),
+            Expr::Import(path) | Expr::FromImport(path, _) => {
+                used.insert(module_global(path));
+            }
+            Expr::TryCatch(body, handler, finally) => {
+                suite_uses(body, used);
+                if let Some((_, handler)) = handler {
+                    suite_uses(handler, used);
+                }
+                suite_uses(finally, used);
+            }
+            Expr::Match(subject, arms) => {
+                value_uses(subject, used);
+                for Arm(patterns, body) in arms {
+                    for pattern in patterns {
+                        pattern_uses(pattern, used);
+                    }
+                    suite_uses(body, used);
+                }
+            }
+            Expr::Destructure(targets, values) => {
+                for target in targets {
+                    destructure_uses(target, used);
+                }
+                for value in values {
+                    value_uses(value, used);
+                }
+            }
+            // tests are only compiled at the top level
+            Expr::Test(_, _) | Expr::Global(_) | Expr::Nonlocal(_) => {}
+        }
+    }
+}
+
+fn value_uses(value: &Value, used: &mut BTreeSet<Identifier>) {
+    match value {
+        // builtins are found by the machine rather than in any scope
+        Value::Name(Name::Name(name)) => {
+            if name.as_builtin().is_none() {
+                used.insert(name.clone());
+            }
+        }
+        Value::Name(name) => target_uses(name, used),
+        Value::Literal(_) => {}
+        Value::FnCall(FnCall(function, arguments)) => {
+            value_uses(function, used);
+            for argument in arguments {
+                value_uses(argument.value(), used);
+            }
+        }
+        Value::Function(function) => used.extend(free_variables(function)),
+        Value::List(_) | Value::Dict(_) | Value::Comprehension(_) | Value::Operation(_, _, _) => {
+            value_uses(&value.desugar(), used)
+        }
+        Value::Await(value) => value_uses(value, used),
+        Value::Conditional(condition, then_value, else_value) => {
+            value_uses(condition, used);
+            value_uses(then_value, used);
+            value_uses(else_value, used);
+        }
+        Value::Slice(value, Slice(start, end, step)) => {
+            value_uses(value, used);
+            for bound in [start, end, step].iter().filter_map(|bound| bound.as_ref()) {
+                value_uses(bound, used);
+            }
+        }
+    }
+}
+
+/// Collects the names referred to by the target of an assignment, which
+/// doesn't refer to a plain name that it binds
+fn target_uses(name: &Name, used: &mut BTreeSet<Identifier>) {
+    match name {
+        Name::Name(_) => {}
+        Name::DotName(head, _) => value_uses(head, used),
+        Name::IndexName(head, indices) => {
+            value_uses(head, used);
+            for index in indices {
+                value_uses(index, used);
+            }
+        }
+    }
+}
+
+fn destructure_uses(target: &Destructure, used: &mut BTreeSet<Identifier>) {
+    match target {
+        Destructure::Name(name) => target_uses(name, used),
+        Destructure::List(targets) => {
+            for target in targets {
+                destructure_uses(target, used);
+            }
+        }
+        Destructure::Dict(_) => {}
+    }
+}
+
+fn pattern_uses(pattern: &Pattern, used: &mut BTreeSet<Identifier>) {
+    match pattern {
+        Pattern::Value(name) => value_uses(&Value::Name(name.clone()), used),
+        Pattern::List(patterns) => {
+            for pattern in patterns {
+                pattern_uses(pattern, used);
+            }
+        }
+        // the enum a variant is named through is compared along with the
+        // variant's name, while a variant named alone refers to nothing
+        Pattern::Variant(name, patterns) => {
+            target_uses(name, used);
+            for pattern in patterns {
+                pattern_uses(pattern, used);
+            }
+        }
+        Pattern::Wildcard | Pattern::Binding(_) | Pattern::Literal(_) => {}
+    }
+}
@@ -0,0 +1,590 @@
+use crate::{capture::declarations, visit::{self, Visit}, Argument, Arm, Comprehension, Destructure, EnumDef, Error, Warning, Pattern, Expr, FnCall, Function, FunctionDef, Identifier, Literal, Name, Parameter, Slice, StructDef, Suite, Type, Value};
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+
+/// The parameters of every name in a scope whose function is known at compile time.
+/// A name that may be bound to anything else maps to `None`.
+type Scope = BTreeMap<Identifier, Option<Vec<Identifier>>>;
+
+/// The name and annotated type of each parameter a function takes
+/// positionally, followed by the type it is annotated to return
+#[derive(Clone, Debug, Default)]
+struct Signature(Vec<(Option<Identifier>, Option<Type>)>, Option<Type>);
+
+/// The types that are known about the names bound in a scope
+#[derive(Clone, Debug, Default)]
+struct Types {
+    variables: BTreeMap<Identifier, Type>,
+    functions: BTreeMap<Identifier, Signature>,
+    /// The signatures of the methods of each class
+    classes: BTreeMap<Identifier, BTreeMap<Identifier, Signature>>,
+    /// The names a function declares global, which are typed by the top level
+    globals: BTreeSet<Identifier>,
+}
+
+
+/// Checks a program for errors that can be found before it runs, such as
+/// keyword arguments that a statically known callee doesn't accept, and
+/// collects warnings about code that is allowed but likely a mistake
+pub fn check(suite: &Suite) -> Result<Vec<Warning>, Error> {
+    let mut checker = Checker {
+        scopes: vec![],
+        types: vec![],
+        asynchronous: vec![],
+        warnings: vec![],
+    };
+    let Suite(exprs) = suite;
+    let mut tests = NestedTests(false);
+    for expr in exprs {
+        match expr {
+            Expr::Test(_, body) => tests.visit_suite(body),
+            expr => tests.visit_expr(expr),
+        }
+    }
+    if tests.0 {
+        return Err(Error::NestedTest);
+    }
+    let mut defers = MisplacedDefers(false);
+    defers.visit_suite(suite);
+    if defers.0 {
+        return Err(Error::DeferOutsideFunction);
+    }
+
+    checker.check_suite(suite)?;
+    Ok(checker.warnings)
+}
+
+/// Finds whether a `test` block is nested anywhere below the top level,
+/// where the test harness would never run it
+struct NestedTests(bool);
+
+impl Visit for NestedTests {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Test(_, _) = expr {
+            self.0 = true;
+        }
+        visit::visit_expr(self, expr)
+    }
+}
+
+/// Finds whether a `defer` is anywhere but directly in the body of a
+/// function or test, where it would run at the end of its block instead of
+/// at the end of the function
+struct MisplacedDefers(bool);
+
+impl MisplacedDefers {
+    fn visit_body(&mut self, body: &Suite) {
+        let Suite(exprs) = body;
+        for expr in exprs {
+            match expr {
+                Expr::Defer(value) => self.visit_value(value),
+                expr => self.visit_expr(expr),
+            }
+        }
+    }
+}
+
+impl Visit for MisplacedDefers {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Defer(_) => self.0 = true,
+            Expr::Test(_, body) => return self.visit_body(body),
+            _ => {}
+        }
+        visit::visit_expr(self, expr)
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        let Function(parameters, body, _, _) = function;
+        for parameter in parameters {
+            self.visit_parameter(parameter);
+        }
+        self.visit_body(body)
+    }
+}
+
+
+fn bind(scope: &mut Scope, name: &Identifier, parameters: Option<Vec<Identifier>>) {
+    let signature = match scope.get(name) {
+        Some(existing) if *existing != parameters => None,
+        _ => parameters,
+    };
+    scope.insert(name.clone(), signature);
+}
+
+fn parameters(function: &Function) -> Option<Vec<Identifier>> {
+    let Function(parameters, _, _, _) = function;
+    Some(
+        parameters
+            .iter()
+            .filter(|p| !matches!(p.untyped(), Parameter::Rest(_)))
+            .filter_map(|p| p.name().cloned())
+            .collect(),
+    )
+}
+
+/// Collects the names bound by a suite, including those bound inside its loops and branches
+fn bindings(suite: &Suite, scope: &mut Scope) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        match expr {
+            Expr::Assignment(Name::Name(name), Value::Function(function))
+            | Expr::FunctionDef(FunctionDef(Name::Name(name), function, _)) => {
+                bind(scope, name, parameters(function))
+            }
+            Expr::Declaration(name, _, Value::Function(function)) => bind(scope, name, parameters(function)),
+            Expr::StructDef(StructDef(Name::Name(name), _, _)) => bind(scope, name, Some(vec![])),
+            Expr::Declaration(name, _, _) => bind(scope, name, None),
+            Expr::Assignment(Name::Name(name), _)
+            | Expr::EnumDef(EnumDef(Name::Name(name), _)) => bind(scope, name, None),
+            Expr::Import(path) => bind(scope, &crate::module_binding(path), None),
+            Expr::FromImport(_, names) => {
+                for name in names {
+                    bind(scope, name, None)
+                }
+            }
+            Expr::Destructure(targets, _) => {
+                for name in targets.iter().flat_map(Destructure::names) {
+                    bind(scope, &name, None);
+                }
+            }
+            Expr::WhileLoop(_, body) => bindings(body, scope),
+            Expr::With(_, name, body) => {
+                bind(scope, name, None);
+                bindings(body, scope);
+            }
+            Expr::TryCatch(body, handler, finally) => {
+                bindings(body, scope);
+                if let Some((name, handler)) = handler {
+                    bind(scope, name, None);
+                    bindings(handler, scope);
+                }
+                bindings(finally, scope);
+            }
+            Expr::IfThenElse(_, then_body, else_body) => {
+                bindings(then_body, scope);
+                bindings(else_body, scope);
+            }
+            Expr::Match(_, arms) => {
+                for Arm(patterns, body) in arms {
+                    for pattern in patterns {
+                        pattern_bindings(pattern, scope);
+                    }
+                    bindings(body, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn signature(function: &Function) -> Signature {
+    let Function(parameters, _, returns, _) = function;
+    Signature(
+        parameters
+            .iter()
+            .take_while(|p| !matches!(p.untyped(), Parameter::Rest(_)))
+            .map(|p| (p.name().cloned(), p.annotation().cloned()))
+            .collect(),
+        returns.clone(),
+    )
+}
+
+/// Collects the types of the annotated variables, functions and classes bound by a suite
+fn annotations(suite: &Suite, types: &mut Types) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        match expr {
+            Expr::Assignment(Name::Name(name), Value::Function(function))
+            | Expr::Declaration(name, None, Value::Function(function))
+            | Expr::FunctionDef(FunctionDef(Name::Name(name), function, _)) => {
+                types.functions.insert(name.clone(), signature(function));
+            }
+            Expr::Declaration(name, Some(annotation), _) => {
+                types.variables.insert(name.clone(), annotation.clone());
+            }
+            Expr::StructDef(StructDef(Name::Name(name), methods, _)) => {
+                let methods = methods
+                    .iter()
+                    .filter_map(|FunctionDef(method, function, _)| match method {
+                        Name::Name(method) => Some((method.clone(), signature(function))),
+                        Name::DotName(_, members) => Some((members[members.len() - 1].clone(), signature(function))),
+                        Name::IndexName(_, _) => None,
+                    })
+                    .collect();
+                types.classes.insert(name.clone(), methods);
+            }
+            Expr::WhileLoop(_, body) | Expr::With(_, _, body) => annotations(body, types),
+            Expr::IfThenElse(_, then_body, else_body) => {
+                annotations(then_body, types);
+                annotations(else_body, types);
+            }
+            Expr::TryCatch(body, handler, finally) => {
+                annotations(body, types);
+                if let Some((_, handler)) = handler {
+                    annotations(handler, types);
+                }
+                annotations(finally, types);
+            }
+            Expr::Match(_, arms) => {
+                for Arm(_, body) in arms {
+                    annotations(body, types);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn pattern_bindings(pattern: &Pattern, scope: &mut Scope) {
+    match pattern {
+        Pattern::Binding(name) => bind(scope, name, None),
+        Pattern::List(patterns) | Pattern::Variant(_, patterns) => {
+            for pattern in patterns {
+                pattern_bindings(pattern, scope);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Checker {
+    scopes: Vec<Scope>,
+    /// The types known about each scope, in the same order as `scopes`
+    types: Vec<Types>,
+    /// Whether each function being checked is asynchronous, innermost last
+    asynchronous: Vec<bool>,
+    warnings: Vec<Warning>,
+}
+
+impl Checker {
+    fn check_suite(&mut self, suite: &Suite) -> Result<(), Error> {
+        let mut scope = Scope::new();
+        bindings(suite, &mut scope);
+        self.check_scope(suite, scope, Types::default())
+    }
+
+    fn check_scope(&mut self, suite: &Suite, scope: Scope, mut types: Types) -> Result<(), Error> {
+        annotations(suite, &mut types);
+        self.scopes.push(scope);
+        self.types.push(types);
+        let result = self.check_exprs(suite);
+        self.scopes.pop();
+        self.types.pop();
+        result
+    }
+
+    /// The position of the innermost scope that binds `name`, or of the
+    /// top level if a function between declares the name global
+    fn scope_of(&self, name: &Identifier) -> Option<usize> {
+        for (i, scope) in self.scopes.iter().enumerate().rev() {
+            if self.types[i].globals.contains(name) {
+                return Some(0).filter(|_| self.scopes[0].contains_key(name));
+            }
+            if scope.contains_key(name) {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// The signature of a function or class method that is known at compile time
+    fn signature(&self, function: &Value) -> Option<&Signature> {
+        match function {
+            Value::Name(Name::Name(name)) => {
+                let scope = self.scope_of(name)?;
+                // a name bound to different functions has no single signature
+                self.scopes[scope].get(name)?.as_ref()?;
+                self.types[scope].functions.get(name)
+            }
+            Value::Name(Name::DotName(head, members)) if members.len() == 1 => {
+                let Type(class) = self.infer(head)?;
+                let scope = self.scope_of(&class)?;
+                self.types[scope].classes.get(&class)?.get(&members[0])
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of a value, if it can be known without running the program
+    fn infer(&self, value: &Value) -> Option<Type> {
+        match value {
+            Value::Literal(Literal::String(_)) => Some(Type::new("str")),
+            Value::Literal(Literal::Number(_)) => Some(Type::new("num")),
+            Value::Literal(Literal::ForeignFunction(_)) | Value::Function(_) => Some(Type::new("fn")),
+            Value::List(_) => Some(Type::new("list")),
+            Value::Dict(_) => Some(Type::new("dict")),
+            Value::Comprehension(comprehension) => match **comprehension {
+                Comprehension::List(_, _) => Some(Type::new("list")),
+                Comprehension::Dict(_, _, _) => Some(Type::new("dict")),
+            },
+            Value::Name(Name::Name(name)) => {
+                let scope = self.scope_of(name)?;
+                self.types[scope].variables.get(name).cloned()
+            }
+            Value::FnCall(FnCall(function, _)) => match &**function {
+                Value::Name(Name::Name(name)) if self.scope_of(name).is_some_and(|scope| {
+                    self.types[scope].classes.contains_key(name) && self.scopes[scope][name].is_some()
+                }) => Some(Type(name.clone())),
+                function => self.signature(function)?.1.clone(),
+            },
+            // an operation on a class is typed by the method that overloads it
+            Value::Operation(left, operator, right) => self.infer(&operator.overload((**left).clone(), (**right).clone())),
+            Value::Conditional(_, then_value, else_value) => {
+                let then_type = self.infer(then_value)?;
+                Some(then_type).filter(|t| Some(t) == self.infer(else_value).as_ref())
+            }
+            _ => None,
+        }
+    }
+
+    /// Fails if `value`, which is bound to `name`, is known to have a type other than `expected`
+    fn expect(&self, name: impl ToString, expected: &Type, value: &Value) -> Result<(), Error> {
+        match self.infer(value) {
+            Some(found) if !expected.accepts(&found) => {
+                Err(Error::TypeMismatch(name.to_string(), expected.clone(), found))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_exprs(&mut self, suite: &Suite) -> Result<(), Error> {
+        let Suite(exprs) = suite;
+        for expr in exprs {
+            match expr {
+                Expr::Assignment(name, value) => {
+                    self.check_name(name)?;
+                    self.check_value(value)?;
+                    if let Name::Name(name) = name {
+                        let declared = self.scope_of(name).and_then(|scope| self.types[scope].variables.get(name));
+                        if let Some(declared) = declared {
+                            self.expect(&name.0, declared, value)?;
+                        }
+                    }
+                }
+                Expr::Declaration(name, annotation, value) => {
+                    self.check_value(value)?;
+                    if let Some(annotation) = annotation {
+                        self.expect(&name.0, annotation, value)?;
+                    }
+                }
+                Expr::WhileLoop(condition, body) => {
+                    self.check_value(condition)?;
+                    self.check_exprs(body)?;
+                }
+                Expr::IfThenElse(condition, then_body, else_body) => {
+                    self.check_value(condition)?;
+                    self.check_exprs(then_body)?;
+                    self.check_exprs(else_body)?;
+                }
+                Expr::FunctionDef(FunctionDef(_, function, _)) => self.check_function(function)?,
+                Expr::StructDef(StructDef(_, methods, _)) => {
+                    for FunctionDef(_, function, _) in methods {
+                        self.check_function(function)?;
+                    }
+                }
+                Expr::TryCatch(body, handler, finally) => {
+                    self.check_exprs(body)?;
+                    if let Some((_, handler)) = handler {
+                        self.check_exprs(handler)?;
+                    }
+                    self.check_exprs(finally)?;
+                }
+                Expr::Match(subject, arms) => {
+                    if !arms.iter().all(|Arm(patterns, _)| patterns.iter().all(Pattern::is_valid)) {
+                        return Err(Error::InvalidPattern);
+                    }
+                    self.check_value(subject)?;
+                    for Arm(_, body) in arms {
+                        self.check_exprs(body)?;
+                    }
+                    if !arms.iter().any(|Arm(patterns, _)| patterns.iter().any(Pattern::is_irrefutable)) {
+                        self.warnings.push(Warning::NonExhaustiveMatch(subject.clone()));
+                    }
+                }
+                Expr::Destructure(targets, values) => {
+                    for target in targets {
+                        self.check_destructure(target)?;
+                    }
+                    for value in values {
+                        self.check_value(value)?;
+                    }
+                }
+                // only the top level scope is outside of every function
+                Expr::Yield(_) if self.scopes.len() == 1 => return Err(Error::YieldOutsideFunction),
+                Expr::Throw(value) | Expr::Yield(value) | Expr::Defer(value) | Expr::Value(value) => {
+                    self.check_value(value)?
+                }
+                Expr::With(value, _, body) => {
+                    self.check_value(value)?;
+                    self.check_exprs(body)?;
+                }
+                Expr::Assert(condition, message) => {
+                    self.check_value(condition)?;
+                    if let Some(message) = message {
+                        self.check_value(message)?;
+                    }
+                }
+                // each test runs in a scope of its own
+                Expr::Test(_, body) => self.check_function(&Function(vec![], body.clone(), None, false))?,
+                Expr::Nonlocal(_) if self.scopes.len() == 1 => return Err(Error::NonlocalOutsideFunction),
+                // a nonlocal name must be bound by an enclosing function, not the top level
+                Expr::Nonlocal(names) => {
+                    let enclosing = &self.scopes[1..self.scopes.len() - 1];
+                    if let Some(Identifier(name)) =
+                        names.iter().find(|name| !enclosing.iter().any(|scope| scope.contains_key(*name)))
+                    {
+                        return Err(Error::UnboundNonlocal(name.clone()));
+                    }
+                }
+                Expr::Global(_) | Expr::EnumDef(_) | Expr::Import(_) | Expr::FromImport(_, _) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn check_function(&mut self, function: &Function) -> Result<(), Error> {
+        let Function(parameters, body, returns, asynchronous) = function;
+        let mut scope = Scope::new();
+        let mut types = Types::default();
+        for parameter in parameters {
+            if let (Some(name), Some(annotation)) = (parameter.name(), parameter.annotation()) {
+                types.variables.insert(name.clone(), annotation.clone());
+            }
+            match parameter.untyped() {
+                Parameter::Default(_, value) => self.check_value(value)?,
+                Parameter::Pattern(pattern) => {
+                    for name in pattern.names() {
+                        scope.insert(name, None);
+                    }
+                }
+                _ => {}
+            }
+            if let Some(name) = parameter.name() {
+                scope.insert(name.clone(), None);
+            }
+        }
+
+        bindings(body, &mut scope);
+        // names declared global or nonlocal are bound, and typed, by the scope they refer to
+        let mut nonlocals = BTreeSet::new();
+        declarations(body, &mut types.globals, &mut nonlocals);
+        scope.retain(|name, _| !types.globals.contains(name) && !nonlocals.contains(name));
+        annotations(body, &mut types);
+        self.scopes.push(scope);
+        self.types.push(types);
+        self.asynchronous.push(*asynchronous);
+        let result = self.check_exprs(body).and_then(|_| match (returns, body.0.last()) {
+            // the value of the last expression is returned
+            (Some(returns), Some(Expr::Value(value))) => self.expect("return", returns, value),
+            _ => Ok(()),
+        });
+        self.scopes.pop();
+        self.types.pop();
+        self.asynchronous.pop();
+        result
+    }
+
+    fn check_destructure(&mut self, target: &Destructure) -> Result<(), Error> {
+        match target {
+            Destructure::Name(name) => self.check_name(name),
+            Destructure::List(targets) => targets.iter().try_for_each(|target| self.check_destructure(target)),
+            Destructure::Dict(_) => Ok(()),
+        }
+    }
+
+    fn check_name(&mut self, name: &Name) -> Result<(), Error> {
+        match name {
+            Name::Name(_) => Ok(()),
+            Name::DotName(head, _) => self.check_value(head),
+            Name::IndexName(head, indices) => {
+                self.check_value(head)?;
+                indices.iter().try_for_each(|index| self.check_value(index))
+            }
+        }
+    }
+
+    fn check_value(&mut self, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Name(name) => self.check_name(name),
+            Value::Literal(_) => Ok(()),
+            Value::Function(function) => self.check_function(function),
+            Value::FnCall(call) => self.check_call(call),
+            Value::Conditional(condition, then_value, else_value) => {
+                self.check_value(condition)?;
+                self.check_value(then_value)?;
+                self.check_value(else_value)
+            }
+            Value::Slice(value, Slice(start, end, step)) => {
+                self.check_value(value)?;
+                [start, end, step]
+                    .iter()
+                    .filter_map(|bound| bound.as_ref())
+                    .try_for_each(|bound| self.check_value(bound))
+            }
+            Value::Operation(left, operator, right) => {
+                self.check_value(&operator.overload((**left).clone(), (**right).clone()))
+            }
+            Value::List(_) | Value::Dict(_) | Value::Comprehension(_) => self.check_value(&value.desugar()),
+            Value::Await(_) if self.asynchronous.last() != Some(&true) => Err(Error::AwaitOutsideAsync),
+            Value::Await(value) => self.check_value(value),
+        }
+    }
+
+    fn check_call(&mut self, call: &FnCall) -> Result<(), Error> {
+        let FnCall(function, arguments) = call;
+        self.check_value(function)?;
+        for argument in arguments {
+            self.check_value(argument.value())?;
+        }
+        self.check_argument_types(call)?;
+
+        let (name, parameters) = match &**function {
+            Value::Name(Name::Name(name)) => {
+                match self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+                    Some(Some(parameters)) => (name.clone(), parameters.clone()),
+                    _ => return Ok(()),
+                }
+            }
+            Value::Function(function) => (Identifier("fn".to_string()), parameters(function).unwrap()),
+            _ => return Ok(()),
+        };
+
+        for argument in arguments {
+            if let Argument::Keyword(keyword, _) = argument {
+                if !parameters.contains(keyword) {
+                    let (Identifier(name), Identifier(keyword)) = (name, keyword.clone());
+                    return Err(Error::UnknownKeyword(name, keyword));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the arguments of a call against the annotated parameters of its callee
+    fn check_argument_types(&self, call: &FnCall) -> Result<(), Error> {
+        let FnCall(function, arguments) = call;
+        let Signature(parameters, _) = match self.signature(function) {
+            Some(signature) => signature,
+            None => return Ok(()),
+        };
+
+        for (i, argument) in arguments.iter().enumerate() {
+            let parameter = match argument {
+                Argument::Value(_) => parameters.get(i),
+                Argument::Keyword(keyword, _) => parameters.iter().find(|(name, _)| name.as_ref() == Some(keyword)),
+                // after a spread, the position of each argument is unknown
+                Argument::Spread(_) => break,
+            };
+            // a parameter without a name, such as a destructured one, is named by its position
+            if let Some((name, Some(annotation))) = parameter {
+                let name = name.as_ref().map_or_else(|| i.to_string(), |Identifier(name)| name.clone());
+                self.expect(name, annotation, argument.value())?;
+            }
+        }
+        Ok(())
+    }
+}
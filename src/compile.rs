@@ -1,13 +1,45 @@
-use crate::{parser::parse, Compile, Target};
+use crate::{parser::parse, module::{link, MemoryLoader, ModuleLoader}, check::check, test_harness, Compile, Suite, Target, Warning};
 
 use alloc::string::String;
+use alloc::vec::Vec;
 
 pub fn compile<T: Target>(script: &str) -> Result<String, String> {
-    match parse(script) {
-        Ok(ast) => match Compile::<T>::compile(ast) {
-            Ok(code_gen) => Ok(code_gen.replace(";", ";\n\t")),
-            Err(e) => Err(format!("{:?}", e)),
-        },
-        Err(e) => Err(format!("{}", e)),
+    compile_with::<T>(script, &MemoryLoader::new())
+}
+
+/// Compiles a script, resolving its imports through `loader`
+pub fn compile_with<T: Target>(script: &str, loader: &impl ModuleLoader) -> Result<String, String> {
+    compile_with_warnings::<T>(script, loader).map(|(code_gen, _)| code_gen)
+}
+
+/// Compiles a script, resolving its imports through `loader`, and returns
+/// the generated code along with the warnings found while checking it
+pub fn compile_with_warnings<T: Target>(script: &str, loader: &impl ModuleLoader) -> Result<(String, Vec<Warning>), String> {
+    generate::<T>(script, loader, |ast| ast)
+}
+
+/// Compiles a script into a harness that runs its `test` blocks and reports their results
+pub fn compile_tests<T: Target>(script: &str) -> Result<String, String> {
+    compile_tests_with::<T>(script, &MemoryLoader::new())
+}
+
+/// Compiles the tests of a script, resolving its imports through `loader`
+pub fn compile_tests_with<T: Target>(script: &str, loader: &impl ModuleLoader) -> Result<String, String> {
+    generate::<T>(script, loader, test_harness).map(|(code_gen, _)| code_gen)
+}
+
+fn generate<T: Target>(
+    script: &str,
+    loader: &impl ModuleLoader,
+    transform: impl FnOnce(Suite) -> Suite,
+) -> Result<(String, Vec<Warning>), String> {
+    let ast = parse(script)?;
+    let result = link(transform(ast), loader).and_then(|ast| {
+        let warnings = check(&ast)?;
+        Ok((Compile::<T>::compile(ast)?, warnings))
+    });
+    match result {
+        Ok((code_gen, warnings)) => Ok((code_gen.replace(";", ";\n\t"), warnings)),
+        Err(e) => Err(format!("{:?}", e)),
     }
 }
@@ -0,0 +1,62 @@
+use crate::visit::{self, Visit};
+use crate::{FunctionDef, Name, Printer, StructDef, Suite, Value};
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+
+/// A function, class or method written with a doc comment
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Doc {
+    /// The name it is defined as, with a method's class before it, such as `Point.new`
+    pub name: String,
+    /// The line it begins with, such as `fn new(self, x, y)` or `class Point`
+    pub signature: String,
+    /// Its doc comment, without the slashes each line begins with
+    pub text: String,
+}
+
+/// The documented definitions in a program, in the order they are defined,
+/// including those defined inside functions
+pub fn docs(suite: &Suite) -> Vec<Doc> {
+    let mut collector = Docs { printer: Printer::default(), docs: vec![] };
+    collector.visit_suite(suite);
+    collector.docs
+}
+
+
+struct Docs {
+    printer: Printer,
+    docs: Vec<Doc>,
+}
+
+impl Docs {
+    fn push(&mut self, name: String, signature: String, doc: &Option<String>) {
+        if let Some(text) = doc {
+            self.docs.push(Doc { name, signature, text: text.clone() });
+        }
+    }
+}
+
+fn name_text(name: &Name) -> String {
+    Value::Name(name.clone()).to_string()
+}
+
+impl Visit for Docs {
+    fn visit_function_def(&mut self, def: &FunctionDef) {
+        let FunctionDef(name, _, doc) = def;
+        self.push(name_text(name), self.printer.signature(def), doc);
+        visit::visit_function_def(self, def)
+    }
+
+    fn visit_struct_def(&mut self, def: &StructDef) {
+        let StructDef(name, methods, doc) = def;
+        let class = name_text(name);
+        self.push(class.clone(), format!("class {}", class), doc);
+        for method in methods {
+            let FunctionDef(name, _, doc) = method;
+            self.push(format!("{}.{}", class, name_text(name)), self.printer.signature(method), doc);
+            visit::visit_function_def(self, method)
+        }
+    }
+}
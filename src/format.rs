@@ -0,0 +1,325 @@
+use crate::parser::{doc_comment, parse, Rule, Xasm};
+use crate::print::{Printer, Trivia};
+
+use pest::iterators::Pair;
+use pest::Parser;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+
+/// Formats xasm source in the style of the printer, keeping its comments,
+/// the blank lines between its statements, and its decorators. Semicolons
+/// are only put after statements the next line would otherwise continue.
+pub fn format(source: &str, printer: &Printer) -> Result<String, String> {
+    let suite = parse(source)?;
+    let program = Xasm::parse(Rule::program, source).map_err(|e| format!("{}", e))?;
+
+    let comments = comments(source);
+    let mut items = vec![];
+    for pair in program {
+        walk(pair, None, &mut items);
+    }
+    for item in &mut items {
+        item.end = end(source, &comments, item);
+    }
+    let mut trivia = vec![Trivia::default(); items.len()];
+    // doc comments are part of the syntax tree, so the printer prints them already
+    let doc = |(start, _): (usize, usize)| {
+        items.iter().any(|item| item.docs.iter().any(|&(from, to)| from <= start && start < to))
+    };
+    for comment in comments.iter().copied().filter(|&comment| !doc(comment)).collect::<Vec<_>>() {
+        attach(source, &comments, &mut items, &mut trivia, comment);
+    }
+    // blank lines are kept before an item and between the comments before it
+    let blank_line = |from: usize, to: usize| source[from..to].matches('\n').count() > 1;
+    for (item, trivia) in items.iter().zip(trivia.iter_mut()) {
+        trivia.decorators = item.decorators;
+        let start = item.leading.first().map_or(item.start, |&(start, _)| start);
+        trivia.blank_line = !item.synthetic && blank_line(source[..start].trim_end().len(), start);
+        // the comments written inside the item come after those written before it
+        let inside = core::mem::take(&mut trivia.leading);
+        for (i, &(start, end)) in item.leading.iter().enumerate() {
+            trivia.leading.push(source[start..end].trim_end().to_string());
+            let next = item.leading.get(i + 1).map_or(item.start, |&(next, _)| next);
+            if blank_line(end, next) {
+                trivia.leading.push(String::new());
+            }
+        }
+        trivia.leading.extend(inside);
+    }
+
+    let result = printer
+        .print_with(&suite, trivia)
+        .ok_or_else(|| "comments could not be matched to the statements they belong to".to_string())?;
+    if parse(&result) != Ok(suite) {
+        return Err("formatting changed the meaning of the program".to_string());
+    }
+    Ok(result)
+}
+
+
+/// A statement, method, match arm, enum variant, or item between brackets
+/// in the source, which comments are kept along with
+struct Item {
+    start: usize,
+    end: usize,
+    /// The innermost item this one is part of
+    parent: Option<usize>,
+    /// The comments on the lines before this item
+    leading: Vec<(usize, usize)>,
+    decorators: Option<(usize, bool)>,
+    /// Whether the printer prints a statement here that the source didn't have
+    synthetic: bool,
+    /// Where each doc comment of a definition begins, and where the
+    /// definition or its decorators begin after it
+    docs: Vec<(usize, usize)>,
+}
+
+/// Collects the items in the order the printer prints them
+fn walk(pair: Pair<Rule>, parent: Option<usize>, items: &mut Vec<Item>) {
+    let rule = pair.as_rule();
+    let children = |pair: Pair<Rule>, parent, items: &mut Vec<Item>| {
+        for child in pair.into_inner() {
+            walk(child, parent, items);
+        }
+    };
+    let item = |pair: &Pair<Rule>, items: &mut Vec<Item>, decorators| {
+        let span = pair.as_span();
+        let definition = match pair.as_rule() {
+            Rule::expr => pair.clone().into_inner().next().unwrap(),
+            _ => pair.clone(),
+        };
+        let mut definitions = vec![definition.clone()];
+        // a decorated definition can have a doc comment after its decorators too
+        if definition.as_rule() == Rule::decorated {
+            definitions.extend(definition.into_inner().last());
+        }
+        let docs = definitions
+            .iter()
+            .filter(|definition| matches!(definition.as_rule(), Rule::function_def | Rule::class_def | Rule::decorated))
+            .filter_map(|definition| doc_comment(definition).map(|(start, _)| (start, definition.as_span().start())))
+            .collect::<Vec<(usize, usize)>>();
+        items.push(Item {
+            start: docs.first().map_or(span.start(), |&(start, _)| start.min(span.start())),
+            end: span.end(),
+            parent,
+            leading: vec![],
+            decorators,
+            synthetic: false,
+            docs,
+        });
+        Some(items.len() - 1)
+    };
+
+    match rule {
+        Rule::expr => {
+            let inner = pair.clone().into_inner().next().unwrap();
+            let decorators = match inner.as_rule() {
+                Rule::decorated => Some((
+                    inner.clone().into_inner().filter(|pair| pair.as_rule() == Rule::decorator).count(),
+                    inner.clone().into_inner().any(|pair| pair.as_rule() == Rule::class_def),
+                )),
+                _ => None,
+            };
+            let index = item(&pair, items, decorators);
+            children(pair, index, items);
+        }
+        Rule::class_def => {
+            for child in pair.into_inner() {
+                match child.as_rule() {
+                    Rule::function_def => {
+                        let index = item(&child, items, None);
+                        children(child, index, items);
+                    }
+                    _ => walk(child, parent, items),
+                }
+            }
+        }
+        Rule::variant => {
+            item(&pair, items, None);
+        }
+        Rule::arm => {
+            let index = item(&pair, items, None);
+            for child in pair.into_inner() {
+                match child.as_rule() {
+                    // a single value in a block is printed without the block, unless it is a dictionary
+                    Rule::suite => match single_value(&child) {
+                        Some(value) if !begins_with_dict(&value) => walk(value, index, items),
+                        _ => walk(child, index, items),
+                    },
+                    // and a dictionary on its own is printed in a block
+                    Rule::value if begins_with_dict(&child) => {
+                        let span = child.as_span();
+                        items.push(Item {
+                            start: span.start(),
+                            end: span.end(),
+                            parent: index,
+                            leading: vec![],
+                            decorators: None,
+                            synthetic: true,
+                            docs: vec![],
+                        });
+                        walk(child, index, items)
+                    }
+                    _ => walk(child, index, items),
+                }
+            }
+        }
+        // the arguments, elements, entries and parameters between brackets keep the comments next to them
+        Rule::call | Rule::list | Rule::dict | Rule::args | Rule::lambda => {
+            for child in pair.into_inner() {
+                match child.as_rule() {
+                    Rule::argument | Rule::element | Rule::entry | Rule::parameter => {
+                        let index = item(&child, items, None);
+                        children(child, index, items);
+                    }
+                    _ => walk(child, parent, items),
+                }
+            }
+        }
+        // a function whose body is a single value is printed as a lambda
+        Rule::function => {
+            for child in pair.into_inner() {
+                match (child.as_rule(), single_value(&child)) {
+                    (Rule::suite, Some(value)) => walk(value, parent, items),
+                    _ => walk(child, parent, items),
+                }
+            }
+        }
+        _ => children(pair, parent, items),
+    }
+}
+
+/// The value a block is made of, if it is made of a single value
+fn single_value<'a>(suite: &Pair<'a, Rule>) -> Option<Pair<'a, Rule>> {
+    let mut exprs = suite.clone().into_inner();
+    match (exprs.next(), exprs.next()) {
+        (Some(expr), None) if suite.as_rule() == Rule::suite => {
+            Some(expr.into_inner().next().unwrap()).filter(|inner| inner.as_rule() == Rule::value)
+        }
+        _ => None,
+    }
+}
+
+fn begins_with_dict(value: &Pair<Rule>) -> bool {
+    let operand = value.clone().into_inner().next().unwrap();
+    let inner = operand.clone().into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::dict | Rule::dict_comprehension => true,
+        Rule::group => begins_with_dict(&inner.into_inner().next().unwrap()),
+        _ => false,
+    }
+}
+
+
+/// The start and end of each comment in some source. Besides doc comments,
+/// comments aren't part of the syntax tree, so they are found here instead.
+fn comments(source: &str) -> Vec<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let mut result = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'"', _) => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            (b'/', Some(b'/')) => {
+                let start = i;
+                while i < bytes.len() && bytes[i] != b'\n' && bytes[i] != b'\r' {
+                    i += 1;
+                }
+                result.push((start, i));
+            }
+            // block comments can be nested
+            (b'/', Some(b'*')) => {
+                let start = i;
+                let mut depth = 0;
+                while i < bytes.len() {
+                    if bytes[i..].starts_with(b"/*") {
+                        depth += 1;
+                        i += 2;
+                    } else if bytes[i..].starts_with(b"*/") {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+                result.push((start, i));
+            }
+            _ => i += 1,
+        }
+    }
+    result
+}
+
+/// Whether there is nothing but whitespace, comments and separators between two positions
+fn blank(source: &str, comments: &[(usize, usize)], mut from: usize, to: usize) -> bool {
+    while from < to {
+        if let Some(&(_, end)) = comments.iter().find(|(start, _)| *start == from) {
+            from = end;
+        } else if source[from..].starts_with(|c: char| c.is_whitespace() || c == ',' || c == ';') {
+            from += source[from..].chars().next().unwrap().len_utf8();
+        } else {
+            return false;
+        }
+    }
+    true
+}
+
+/// The end of an item, leaving out any whitespace and comments its span ends with
+fn end(source: &str, comments: &[(usize, usize)], item: &Item) -> usize {
+    let mut end = item.end;
+    loop {
+        if let Some(&(start, _)) = comments.iter().find(|&&(start, comment)| comment == end && start >= item.start) {
+            end = start;
+        } else if source[..end].ends_with(char::is_whitespace) {
+            end -= source[..end].chars().next_back().unwrap().len_utf8();
+        } else {
+            return end;
+        }
+    }
+}
+
+/// Keeps a comment with the item it is written next to: at the end of the
+/// line of the item before it, on the lines before the item after it, or at
+/// the end of a block after its last item. Otherwise it goes before the item
+/// it is written inside of.
+fn attach(source: &str, comments: &[(usize, usize)], items: &mut [Item], trivia: &mut [Trivia], comment: (usize, usize)) {
+    let (start, end) = comment;
+    let text = source[start..end].trim_end().to_string();
+    let enclosing = (0..items.len()).rev().find(|&i| items[i].start < start && start < items[i].end);
+    let siblings = (0..items.len())
+        .filter(|&i| items[i].parent == enclosing && !items[i].synthetic)
+        .collect::<Vec<usize>>();
+
+    let previous = siblings.iter().copied().rfind(|&i| items[i].end <= start);
+    let next = siblings.iter().copied().find(|&i| items[i].start >= end);
+    match (previous, next) {
+        (Some(previous), _)
+            if !source[items[previous].end..start].contains('\n')
+                && blank(source, comments, items[previous].end, start) =>
+        {
+            trivia[previous].trailing.push(text)
+        }
+        (_, Some(next)) if blank(source, comments, end, items[next].start) => {
+            items[next].leading.push(comment);
+        }
+        (Some(previous), _) if blank(source, comments, items[previous].end, start) => {
+            trivia[previous].after.push(text)
+        }
+        _ => match enclosing {
+            Some(enclosing) => trivia[enclosing].leading.push(text),
+            // the program always has an item, so this is a comment before the first one
+            None => trivia[0].leading.push(text),
+        },
+    }
+}
@@ -1,5 +1,5 @@
 
-use crate::{Target, Compile, MACHINE_NAME, Value, Identifier};
+use crate::{keyword_names, keyword_values, Target, MACHINE_NAME, Identifier};
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -19,6 +19,10 @@ impl Target for Golang {
         )
     }
 
+    fn builtin(name: impl ToString) -> String {
+        format!("{}.Builtin({})\n", MACHINE_NAME, Self::quote(name))
+    }
+
     fn number(number: impl ToString) -> String {
         format!("NewNumber({})", number.to_string())
     }
@@ -27,6 +31,11 @@ impl Target for Golang {
         format!("NewString({})", string.to_string())
     }
 
+    fn names(names: Vec<String>) -> String {
+        let names = names.iter().map(Self::quote).collect::<Vec<String>>();
+        format!("[]string{{{}}}", names.join(", "))
+    }
+
     fn store(value: impl ToString) -> String {
         format!("{}{}.Store()\n", value.to_string(), MACHINE_NAME)
     }
@@ -43,6 +52,34 @@ impl Target for Golang {
         )
     }
 
+    fn generator(body: impl ToString) -> String {
+        format!(
+            "NewGenerator(func({MACHINE} *Machine) {{{func}}}, {MACHINE}.Duplicate())",
+            func = body.to_string(),
+            MACHINE = MACHINE_NAME
+        )
+    }
+
+    fn async_func(body: impl ToString) -> String {
+        format!(
+            "NewAsyncFunction(func({MACHINE} *Machine) {{{func}}}, {MACHINE}.Duplicate())",
+            func = body.to_string(),
+            MACHINE = MACHINE_NAME
+        )
+    }
+
+    fn capture(variables: Vec<String>) -> String {
+        format!("{}.Capture({})\n", MACHINE_NAME, Self::names(variables))
+    }
+
+    fn global(names: Vec<String>) -> String {
+        format!("{}.Global({})\n", MACHINE_NAME, Self::names(names))
+    }
+
+    fn nonlocal(names: Vec<String>) -> String {
+        format!("{}.Nonlocal({})\n", MACHINE_NAME, Self::names(names))
+    }
+
     fn foreign_func(name: impl ToString) -> String {
         format!(
             "NewFunction({name}, {MACHINE}.Duplicate())",
@@ -74,22 +111,99 @@ impl Target for Golang {
         )
     }
 
-    fn call(func: impl ToString) -> String {
+    fn call(func: impl ToString, argc: impl ToString, keywords: Vec<(String, String)>) -> String {
+        if keywords.is_empty() {
+            format!(
+                "{argc}{func}{MACHINE}.Call()\n",
+                MACHINE = MACHINE_NAME,
+                argc = argc.to_string(),
+                func = func.to_string()
+            )
+        } else {
+            format!(
+                "{argc}{values}{func}{MACHINE}.KeywordCall({keywords})\n",
+                MACHINE = MACHINE_NAME,
+                argc = argc.to_string(),
+                values = keyword_values(&keywords),
+                func = func.to_string(),
+                keywords = keyword_names::<Self>(&keywords)
+            )
+        }
+    }
+
+    fn method_call(
+        method_name: impl ToString,
+        argc: impl ToString,
+        keywords: Vec<(String, String)>,
+    ) -> String {
+        if keywords.is_empty() {
+            format!(
+                "{argc}{method_name}{MACHINE}.MethodCall()\n",
+                argc = argc.to_string(),
+                method_name = Self::push(method_name.to_string()),
+                MACHINE = MACHINE_NAME
+            )
+        } else {
+            format!(
+                "{argc}{values}{method_name}{MACHINE}.KeywordMethodCall({keywords})\n",
+                argc = argc.to_string(),
+                values = keyword_values(&keywords),
+                method_name = Self::push(method_name.to_string()),
+                keywords = keyword_names::<Self>(&keywords),
+                MACHINE = MACHINE_NAME
+            )
+        }
+    }
+
+    fn arguments(parameters: Vec<String>, defaults: Vec<String>, variadic: bool) -> String {
         format!(
-            "{func}{MACHINE}.Call()\n",
-            MACHINE = MACHINE_NAME,
-            func = func.to_string()
+            "{defaults}{MACHINE}.Arguments({parameters}, {count}, {variadic})\n",
+            defaults = defaults
+                .iter()
+                .rev()
+                .map(|default| Self::push(Self::func(default)))
+                .collect::<String>(),
+            parameters = Self::names(parameters),
+            count = defaults.len(),
+            variadic = variadic,
+            MACHINE = MACHINE_NAME
         )
     }
 
-    fn method_call(method_name: impl ToString) -> String {
+    fn argument(value: impl ToString) -> String {
+        format!("{}{}.Argument()\n", value.to_string(), MACHINE_NAME)
+    }
+
+    fn spread(list: impl ToString) -> String {
+        format!("{}{}.Spread()\n", list.to_string(), MACHINE_NAME)
+    }
+
+    fn throw(value: impl ToString) -> String {
         format!(
-            "{method_name}{MACHINE}.MethodCall()\n",
-            method_name = Self::push(method_name.to_string()),
+            "{value}{MACHINE}.Throw()\n",
+            value = value.to_string(),
             MACHINE = MACHINE_NAME
         )
     }
 
+    fn try_catch(body: impl ToString, handler: impl ToString, finally: impl ToString) -> String {
+        format!(
+            "{finally}{handler}{body}{MACHINE}.TryCatch()\n",
+            MACHINE = MACHINE_NAME,
+            body = Self::push(Self::func(body)),
+            handler = Self::push(Self::func(handler)),
+            finally = Self::push(Self::func(finally))
+        )
+    }
+
+    fn yield_value(value: impl ToString) -> String {
+        format!("{}{}.Yield()\n", value.to_string(), MACHINE_NAME)
+    }
+
+    fn await_value(value: impl ToString) -> String {
+        format!("{}{}.Await()\n", value.to_string(), MACHINE_NAME)
+    }
+
     fn assign(pointer_value: impl ToString) -> String {
         format!(
             "{pointer_value}{MACHINE}.Assign()\n",
@@ -98,8 +212,29 @@ impl Target for Golang {
         )
     }
 
-    fn dotname(head: Value, tail: Vec<Identifier>) -> String {
-        let mut result = Compile::<Self>::compile(head).unwrap();
+    fn slice(
+        value: impl ToString,
+        start: Option<String>,
+        end: Option<String>,
+        step: Option<String>,
+    ) -> String {
+        format!(
+            "{value}{bounds}{MACHINE}.Slice({start}, {end}, {step})\n",
+            value = value.to_string(),
+            bounds = [&start, &end, &step]
+                .iter()
+                .filter_map(|bound| bound.as_ref())
+                .cloned()
+                .collect::<String>(),
+            start = start.is_some(),
+            end = end.is_some(),
+            step = step.is_some(),
+            MACHINE = MACHINE_NAME
+        )
+    }
+
+    fn dotname(head: impl ToString, tail: Vec<Identifier>) -> String {
+        let mut result = head.to_string();
         for ident in tail {
             let Identifier(name) = ident;
             result += &(Self::push(Self::string(Self::quote(name))) + &format!("{}.Index()\n", MACHINE_NAME));
@@ -107,12 +242,13 @@ impl Target for Golang {
         result
     }
 
-    fn indexname(head: Value, tail: Vec<Value>) -> String {
-        let mut result = Compile::<Self>::compile(head).unwrap();
+    fn indexname(head: impl ToString, tail: Vec<String>) -> String {
+        let mut result = head.to_string();
         for value in tail {
-            result += &(Compile::<Self>::compile(value).unwrap() + &format!("{}.Index()\n", MACHINE_NAME));
+            result += &(value + &format!("{}.Index()\n", MACHINE_NAME));
         }
         result
     }
 
-}
\ No newline at end of file
+}
+
@@ -14,6 +14,27 @@ pub use parser::*;
 mod compile;
 pub use compile::*;
 
+mod module;
+pub use module::*;
+
+mod check;
+pub use check::*;
+
+mod capture;
+pub use capture::*;
+
+mod print;
+pub use print::*;
+
+mod format;
+pub use format::*;
+
+mod doc;
+pub use doc::*;
+
+pub mod visit;
+pub use visit::{Fold, Visit, VisitMut};
+
 
 mod rust;
 pub use rust::*;
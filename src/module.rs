@@ -0,0 +1,265 @@
+use crate::visit::{self, Visit, VisitMut};
+use crate::{builtin, check::check, parser::parse, Destructure, EnumDef, Error, Expr, FnCall, Function, FunctionDef, Identifier, Name, StructDef, Suite, Value};
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use std::path::PathBuf;
+
+
+/// The extension appended to module paths that don't already have one
+pub const MODULE_EXTENSION: &str = "xasm";
+
+/// The name of the dictionary a module's top level names are exported through
+const MODULE_NAME: &str = "__module__";
+
+
+/// Retrieves the source code of a module from its import path
+pub trait ModuleLoader {
+    fn load(&self, path: &str) -> Result<String, Error>;
+}
+
+
+/// Loads modules from files relative to a root directory
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct FileLoader(pub PathBuf);
+
+impl ModuleLoader for FileLoader {
+    fn load(&self, path: &str) -> Result<String, Error> {
+        let FileLoader(root) = self;
+        let mut file = root.join(path);
+        if file.extension().is_none() {
+            file.set_extension(MODULE_EXTENSION);
+        }
+
+        std::fs::read_to_string(file).map_err(|_| Error::ModuleNotFound(path.to_string()))
+    }
+}
+
+
+/// Loads modules from sources kept in memory, keyed by import path
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Eq, Ord)]
+pub struct MemoryLoader(pub BTreeMap<String, String>);
+
+impl MemoryLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, path: impl ToString, source: impl ToString) -> Self {
+        self.0.insert(path.to_string(), source.to_string());
+        self
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn load(&self, path: &str) -> Result<String, Error> {
+        let MemoryLoader(modules) = self;
+        let path = normalize(path);
+        modules
+            .iter()
+            .find(|(key, _)| normalize(key) == path)
+            .map(|(_, source)| source.clone())
+            .ok_or(Error::ModuleNotFound(path))
+    }
+}
+
+
+/// The path a module is identified by: without its extension, and with
+/// `.` and `..` components resolved, so that `./m`, `m` and `m.xasm` all
+/// refer to the same module
+pub fn normalize(path: &str) -> String {
+    let suffix = format!(".{}", MODULE_EXTENSION);
+    let mut components: Vec<&str> = vec![];
+    for component in path.trim_end_matches(suffix.as_str()).split('/') {
+        match component {
+            "" | "." => {}
+            ".." if components.last().is_some_and(|last| *last != "..") => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
+/// Resolves an import path written in the module at `importer`. Paths
+/// beginning with `./` or `../` are relative to the directory of the
+/// importing module, and any other path is relative to the root.
+pub fn resolve(importer: &str, path: &str) -> String {
+    if path.starts_with("./") || path.starts_with("../") {
+        let directory = importer.rsplit_once('/').map_or("", |(directory, _)| directory);
+        normalize(&format!("{}/{}", directory, path))
+    } else {
+        normalize(path)
+    }
+}
+
+
+/// The name an `import` statement binds a module to: the last component of its path
+pub fn module_binding(path: &str) -> Identifier {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let suffix = format!(".{}", MODULE_EXTENSION);
+    Identifier(file.trim_end_matches(suffix.as_str()).to_string())
+}
+
+/// The global that holds a module's namespace once it has been evaluated
+pub fn module_global(path: &str) -> Identifier {
+    Identifier(format!("{}{}", MODULE_NAME, path))
+}
+
+
+/// Resolves every import reachable from `program`, and prepends the
+/// evaluation of each module, in dependency order, to the program.
+/// Each module is evaluated exactly once, no matter how many times or by
+/// which of the paths that resolve to it it is imported.
+pub fn link(mut program: Suite, loader: &impl ModuleLoader) -> Result<Suite, Error> {
+    let mut linker = Linker {
+        loader,
+        visiting: vec![],
+        linked: BTreeSet::new(),
+        prelude: vec![],
+    };
+
+    Resolver("").visit_suite_mut(&mut program);
+    for path in imports(&program) {
+        linker.link(path)?;
+    }
+
+    let Suite(exprs) = program;
+    let mut result = linker.prelude;
+    result.extend(exprs);
+    Ok(Suite(result))
+}
+
+
+struct Linker<'a, L: ModuleLoader> {
+    loader: &'a L,
+    /// The chain of modules currently being resolved, used to detect cycles
+    visiting: Vec<String>,
+    linked: BTreeSet<String>,
+    prelude: Vec<Expr>,
+}
+
+impl<'a, L: ModuleLoader> Linker<'a, L> {
+    fn link(&mut self, path: String) -> Result<(), Error> {
+        if self.linked.contains(&path) {
+            return Ok(());
+        }
+
+        if let Some(start) = self.visiting.iter().position(|p| *p == path) {
+            let mut cycle = self.visiting[start..].to_vec();
+            cycle.push(path);
+            return Err(Error::CyclicImport(cycle));
+        }
+
+        let source = self.loader.load(&path)?;
+        let mut module = parse(&source).map_err(|e| Error::ModuleSyntax(path.clone(), e))?;
+        Resolver(&path).visit_suite_mut(&mut module);
+        // once the module is wrapped in a function, its top level would be checked as a function body,
+        // and its warnings are found again when the whole program is checked
+        check(&module)?;
+        let mut globals = Globals(false);
+        globals.visit_suite(&module);
+        if globals.0 {
+            return Err(Error::GlobalInModule(path));
+        }
+        // only the tests of the program being compiled are run, and not those of the modules it imports
+        module.0.retain(|expr| !matches!(expr, Expr::Test(_, _)));
+
+        self.visiting.push(path.clone());
+        for dependency in imports(&module) {
+            self.link(dependency)?;
+        }
+        self.visiting.pop();
+
+        self.prelude.push(Expr::Assignment(
+            Name::Name(module_global(&path)),
+            Value::FnCall(FnCall(Box::new(Value::Function(namespace(module))), vec![])),
+        ));
+        self.linked.insert(path);
+        Ok(())
+    }
+}
+
+
+/// Rewrites the paths of the imports in a module to the modules they resolve to
+struct Resolver<'a>(&'a str);
+
+impl<'a> VisitMut for Resolver<'a> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Import(path) | Expr::FromImport(path, _) = expr {
+            *path = resolve(self.0, path);
+        }
+        visit::visit_expr_mut(self, expr)
+    }
+}
+
+
+/// Finds whether a module declares any name global anywhere in it
+struct Globals(bool);
+
+impl Visit for Globals {
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.0 |= matches!(expr, Expr::Global(_));
+        visit::visit_expr(self, expr)
+    }
+}
+
+
+/// Wraps a module's body in a function that returns a dictionary of its top level names
+fn namespace(module: Suite) -> Function {
+    let module_name = || Identifier(MODULE_NAME.to_string());
+    let Suite(mut exprs) = module;
+
+    let exports = exprs
+        .iter()
+        .flat_map(|expr| match expr {
+            Expr::Assignment(Name::Name(name), _) | Expr::Declaration(name, _, _) => vec![name.clone()],
+            Expr::Destructure(targets, _) => targets.iter().flat_map(Destructure::names).collect(),
+            Expr::FunctionDef(FunctionDef(Name::Name(name), _, _)) => vec![name.clone()],
+            Expr::StructDef(StructDef(Name::Name(name), _, _)) => vec![name.clone()],
+            Expr::EnumDef(EnumDef(Name::Name(name), _)) => vec![name.clone()],
+            Expr::Import(path) => vec![module_binding(path)],
+            Expr::FromImport(_, names) => names.clone(),
+            _ => vec![],
+        })
+        .collect::<BTreeSet<Identifier>>();
+
+    exprs.push(Expr::Assignment(
+        Name::Name(module_name()),
+        builtin("dict", vec![]),
+    ));
+    for name in exports {
+        exprs.push(Expr::Assignment(
+            Name::DotName(Box::new(Value::Name(Name::Name(module_name()))), vec![name.clone()]),
+            Value::Name(Name::Name(name)),
+        ));
+    }
+    exprs.push(Expr::Value(Value::Name(Name::Name(module_name()))));
+
+    Function(vec![], Suite(exprs), None, false)
+}
+
+
+/// Every module path imported anywhere in a suite, in order of appearance
+fn imports(suite: &Suite) -> Vec<String> {
+    let mut imports = Imports(vec![]);
+    imports.visit_suite(suite);
+    imports.0
+}
+
+
+/// Collects the paths of the imports in a module, including those nested in its functions
+struct Imports(Vec<String>);
+
+impl Visit for Imports {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Import(path) | Expr::FromImport(path, _) = expr {
+            self.0.push(path.clone());
+        }
+        visit::visit_expr(self, expr)
+    }
+}
@@ -1,7 +1,6 @@
 
-use pest::*;
 use pest::{Parser, iterators::{Pair, Pairs}};
-use crate::{token::*, rust::Rust, compile::compile, target::Compile};
+use crate::token::*;
 
 
 #[derive(Parser)]
@@ -14,61 +13,127 @@ fn identifier(pair: Pair<Rule>) -> Identifier {
     Identifier(pair.as_span().as_str().trim().to_string())
 }
 
-fn dot_identifier(mut pairs: Pairs<Rule>) -> Name {
-    Name::DotName(
-        Box::new({
-            let accessing = pairs.next().unwrap();
-            match accessing.as_rule() {
-                Rule::identifier => Value::Name(Name::Name(identifier(accessing))),
-                Rule::group => value(accessing.into_inner().into_iter().next().unwrap()),
-                _ => unreachable!()
-            }
-        }),
-        pairs.into_iter()
-            .map(identifier)
-            .collect::<Vec<Identifier>>()
-    )
+/// Applies each member access, index, slice and call in a chain
+/// to the value before it, from left to right
+fn postfix(pairs: Pairs<Rule>) -> Value {
+    let mut result = None;
+    for pair in pairs {
+        result = Some(match (result, pair.as_rule()) {
+            (None, Rule::identifier) => Value::Name(Name::Name(identifier(pair))),
+            (None, Rule::group) => value(pair),
+            (None, _) => collection(pair),
+            (Some(Value::Name(Name::DotName(head, mut members))), Rule::member) => {
+                members.push(identifier(pair.into_inner().next().unwrap()));
+                Value::Name(Name::DotName(head, members))
+            },
+            (Some(operand), Rule::member) => Value::Name(Name::DotName(
+                Box::new(operand),
+                vec![identifier(pair.into_inner().next().unwrap())]
+            )),
+            (Some(Value::Name(Name::IndexName(head, mut indices))), Rule::index) => {
+                indices.push(value(pair.into_inner().next().unwrap()));
+                Value::Name(Name::IndexName(head, indices))
+            },
+            (Some(operand), Rule::index) => Value::Name(Name::IndexName(
+                Box::new(operand),
+                vec![value(pair.into_inner().next().unwrap())]
+            )),
+            (Some(operand), Rule::slice) => Value::Slice(Box::new(operand), slice(pair.into_inner())),
+            (Some(operand), Rule::call) => Value::FnCall(FnCall(
+                Box::new(operand),
+                pair.into_inner().map(argument).collect()
+            )),
+            _ => unreachable!()
+        });
+    }
+    result.unwrap()
 }
 
-fn index_identifier(mut pairs: Pairs<Rule>) -> Name {
-    Name::IndexName(
-        Box::new({
-            let accessing = pairs.next().unwrap();
-            match accessing.as_rule() {
-                Rule::identifier => Value::Name(Name::Name(identifier(accessing))),
-                Rule::group => value(accessing.into_inner().into_iter().next().unwrap()),
-                _ => unreachable!()
-            }
-        }),
-        pairs.into_iter()
-            .map(value)
-            .collect::<Vec<Value>>()
-    )
+fn name(pair: Pair<Rule>) -> Name {
+    match postfix(pair.into_inner()) {
+        Value::Name(name) => name,
+        _ => unreachable!()
+    }
 }
 
-fn name(pair: Pair<Rule>) -> Name {
+fn slice(pairs: Pairs<Rule>) -> Slice {
+    let mut bounds = Slice(None, None, None);
+    for pair in pairs {
+        let rule = pair.as_rule();
+        let bound = Some(Box::new(value(pair.into_inner().next().unwrap())));
+        match rule {
+            Rule::slice_start => bounds.0 = bound,
+            Rule::slice_end => bounds.1 = bound,
+            Rule::slice_step => bounds.2 = bound,
+            _ => unreachable!()
+        }
+    }
+    bounds
+}
+
+fn argument(pair: Pair<Rule>) -> Argument {
     let pair = pair.into_inner().next().unwrap();
     match pair.as_rule() {
-        Rule::dot_identifier => dot_identifier(pair.into_inner()),
-        Rule::index_identifier => index_identifier(pair.into_inner()),
-        Rule::identifier => Name::Name(identifier(pair)),
-        Rule::name => name(pair),
+        Rule::spread => Argument::Spread(value(pair.into_inner().next().unwrap())),
+        Rule::keyword_argument => {
+            let mut pairs = pair.into_inner();
+            Argument::Keyword(
+                identifier(pairs.next().unwrap()),
+                value(pairs.next().unwrap())
+            )
+        },
+        Rule::value => Argument::Value(value(pair)),
         _ => unreachable!()
     }
 }
 
-fn fncall(pair: Pair<Rule>) -> FnCall {
-    let mut pairs = pair.clone().into_inner();
-    let call_operand = pairs.next().unwrap();
-    FnCall(
-        Box::new(match call_operand.as_rule() {
-            Rule::name => Value::Name(name(call_operand)),
-            Rule::group => value(call_operand),
-            Rule::fncall => Value::FnCall(fncall(call_operand)),
-            _ => unreachable!()
-        }),
-        pairs.into_iter().map(value).collect()
-    )
+fn element(pair: Pair<Rule>) -> Element {
+    let pair = pair.into_inner().next().unwrap();
+    match pair.as_rule() {
+        Rule::spread => Element::Spread(value(pair.into_inner().next().unwrap())),
+        Rule::value => Element::Value(value(pair)),
+        _ => unreachable!()
+    }
+}
+
+fn entry(pair: Pair<Rule>) -> Entry {
+    let mut pairs = pair.into_inner();
+    let first = pairs.next().unwrap();
+    match first.as_rule() {
+        Rule::spread => Entry::Spread(value(first.into_inner().next().unwrap())),
+        Rule::value => Entry::Pair(value(first), value(pairs.next().unwrap())),
+        _ => unreachable!()
+    }
+}
+
+fn clause(pair: Pair<Rule>) -> Clause {
+    let rule = pair.as_rule();
+    let mut pairs = pair.into_inner();
+    match rule {
+        Rule::for_clause => Clause::For(
+            destructure(pairs.next().unwrap()),
+            value(pairs.next().unwrap())
+        ),
+        Rule::if_clause => Clause::If(value(pairs.next().unwrap())),
+        _ => unreachable!()
+    }
+}
+
+fn comprehension(pair: Pair<Rule>) -> Comprehension {
+    let rule = pair.as_rule();
+    let mut pairs = pair.into_inner();
+    match rule {
+        Rule::list_comprehension => Comprehension::List(
+            value(pairs.next().unwrap()),
+            pairs.map(clause).collect()
+        ),
+        Rule::dict_comprehension => Comprehension::Dict(
+            value(pairs.next().unwrap()),
+            value(pairs.next().unwrap()),
+            pairs.map(clause).collect()
+        ),
+        _ => unreachable!()
+    }
 }
 
 fn literal(pair: Pair<Rule>) -> Literal {
@@ -84,15 +149,93 @@ fn literal(pair: Pair<Rule>) -> Literal {
     }    
 }
 
+fn conditional(mut pairs: Pairs<Rule>) -> Value {
+    let condition = value(pairs.next().unwrap());
+    let then_value = value(pairs.next().unwrap());
+    let else_value = pairs.next().unwrap();
+    Value::Conditional(
+        Box::new(condition),
+        Box::new(then_value),
+        Box::new(match else_value.as_rule() {
+            Rule::conditional => conditional(else_value.into_inner()),
+            _ => value(else_value),
+        })
+    )
+}
+
+fn operator(pair: Pair<Rule>) -> Operator {
+    match pair.as_str() {
+        "==" => Operator::Equal,
+        "!=" => Operator::NotEqual,
+        "<=" => Operator::LessEqual,
+        ">=" => Operator::GreaterEqual,
+        "<" => Operator::Less,
+        ">" => Operator::Greater,
+        "+" => Operator::Add,
+        "-" => Operator::Subtract,
+        "*" => Operator::Multiply,
+        "/" => Operator::Divide,
+        "%" => Operator::Remainder,
+        _ => unreachable!()
+    }
+}
+
+/// Combines operands with the operators between them, applying operators
+/// with a higher precedence first, and operators of equal precedence from the left
 fn value(pair: Pair<Rule>) -> Value {
+    if pair.as_rule() == Rule::group {
+        return value(pair.into_inner().next().unwrap());
+    }
+
+    let mut values = vec![];
+    let mut operators: Vec<Operator> = vec![];
+    let apply = |values: &mut Vec<Value>, operator| {
+        let right = values.pop().unwrap();
+        let left = values.pop().unwrap();
+        values.push(Value::Operation(Box::new(left), operator, Box::new(right)));
+    };
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::operand => values.push(operand(pair)),
+            Rule::operator => {
+                let operator = operator(pair);
+                while operators.last().is_some_and(|last| last.precedence() >= operator.precedence()) {
+                    apply(&mut values, operators.pop().unwrap());
+                }
+                operators.push(operator);
+            },
+            _ => unreachable!()
+        }
+    }
+    while let Some(operator) = operators.pop() {
+        apply(&mut values, operator);
+    }
+    values.pop().unwrap()
+}
+
+fn operand(pair: Pair<Rule>) -> Value {
     let pair = pair.into_inner().next().unwrap();
     match pair.as_rule() {
-        Rule::function => Value::Function(function(pair)),
-        Rule::fncall => Value::FnCall(fncall(pair)),
+        Rule::await_value => Value::Await(Box::new(operand(pair.into_inner().next().unwrap()))),
+        Rule::conditional => conditional(pair.into_inner()),
+        Rule::function => Value::Function(function(pair.into_inner())),
+        Rule::lambda => Value::Function(function(pair.into_inner())),
+        Rule::postfix => postfix(pair.into_inner()),
         Rule::name => Value::Name(name(pair)),
         Rule::literal => Value::Literal(literal(pair)),
         Rule::group => value(pair),
-        Rule::value => value(pair),
+        _ => collection(pair)
+    }
+}
+
+/// A list or dictionary, whether written out or as a comprehension
+fn collection(pair: Pair<Rule>) -> Value {
+    match pair.as_rule() {
+        Rule::list => Value::List(pair.into_inner().map(element).collect()),
+        Rule::dict => Value::Dict(pair.into_inner().map(entry).collect()),
+        Rule::list_comprehension | Rule::dict_comprehension => {
+            Value::Comprehension(Box::new(comprehension(pair)))
+        },
         _ => unreachable!()
     }
 }
@@ -105,44 +248,237 @@ fn assignment(mut pairs: Pairs<Rule>) -> Expr {
     )
 }
 
-fn args(pair: Pair<Rule>) -> Vec<Identifier> {
-    pair.into_inner().into_iter().map(identifier).collect()
+fn type_name(pair: Pair<Rule>) -> Type {
+    Type(identifier(pair))
+}
+
+fn declaration(pairs: Pairs<Rule>) -> Expr {
+    let mut name = None;
+    let mut annotation = None;
+    let mut initial = None;
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::identifier => name = Some(identifier(pair)),
+            Rule::type_name => annotation = Some(type_name(pair)),
+            Rule::value => initial = Some(value(pair)),
+            _ => unreachable!()
+        }
+    }
+    Expr::Declaration(name.unwrap(), annotation, initial.unwrap())
+}
+
+fn parameter(pair: Pair<Rule>) -> Parameter {
+    let pair = pair.into_inner().next().unwrap();
+    match pair.as_rule() {
+        Rule::rest_parameter => Parameter::Rest(identifier(pair.into_inner().next().unwrap())),
+        Rule::default_parameter => {
+            let mut pairs = pair.into_inner();
+            Parameter::Default(
+                identifier(pairs.next().unwrap()),
+                value(pairs.next().unwrap())
+            )
+        },
+        Rule::typed_parameter => {
+            let mut pairs = pair.into_inner();
+            let name = identifier(pairs.next().unwrap());
+            let annotation = type_name(pairs.next().unwrap());
+            let parameter = match pairs.next() {
+                Some(default) => Parameter::Default(name, value(default)),
+                None => Parameter::Name(name),
+            };
+            Parameter::Typed(Box::new(parameter), annotation)
+        },
+        Rule::list_destructure | Rule::dict_destructure => Parameter::Pattern(destructure(pair)),
+        Rule::identifier => Parameter::Name(identifier(pair)),
+        _ => unreachable!()
+    }
+}
+
+fn destructure(pair: Pair<Rule>) -> Destructure {
+    match pair.as_rule() {
+        Rule::list_destructure => Destructure::List(pair.into_inner().map(destructure).collect()),
+        Rule::dict_destructure => Destructure::Dict(pair.into_inner().map(identifier).collect()),
+        Rule::name => Destructure::Name(name(pair)),
+        Rule::destructure_target => destructure(pair.into_inner().next().unwrap()),
+        _ => unreachable!()
+    }
+}
+
+fn destructuring(pairs: Pairs<Rule>) -> Expr {
+    let mut targets = vec![];
+    let mut values = vec![];
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::value => values.push(value(pair)),
+            _ => targets.push(destructure(pair)),
+        }
+    }
+    Expr::Destructure(targets, values)
+}
+
+fn args(pair: Pair<Rule>) -> Vec<Parameter> {
+    pair.into_inner().map(parameter).collect()
 }
 
 fn suite(pair: Pair<Rule>) -> Suite {
-    Suite(pair.into_inner().into_iter().map(expr).collect())
+    Suite(pair.into_inner().flat_map(statements).collect())
 }
 
-fn function(pair: Pair<Rule>) -> Function {
-    let mut pairs = pair.into_inner();
-    Function(
-        args(pairs.next().unwrap()),
-        suite(pairs.next().unwrap())
-    )
+/// A function, a lambda, or the part of a function definition after its name
+fn function(pairs: Pairs<Rule>) -> Function {
+    let mut parameters = vec![];
+    let mut body = Suite(vec![]);
+    let mut returns = None;
+    let mut asynchronous = false;
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::asynchronous => asynchronous = true,
+            Rule::args => parameters = args(pair),
+            Rule::parameter => parameters.push(parameter(pair)),
+            Rule::type_name => returns = Some(type_name(pair)),
+            // the name of a function definition
+            Rule::name => {},
+            Rule::suite => body = suite(pair),
+            Rule::value => body = Suite(vec![Expr::Value(value(pair))]),
+            _ => unreachable!()
+        }
+    }
+    Function(parameters, body, returns, asynchronous)
+}
+
+/// The `///` comments on the lines directly before a definition, without
+/// their slashes and the space after them, and where the first one starts.
+/// Comments beginning with four slashes are ordinary comments.
+pub(crate) fn doc_comment(pair: &Pair<Rule>) -> Option<(usize, String)> {
+    let span = pair.as_span();
+    let input = span.get_input();
+    let mut line_start = input[..span.start()].rfind('\n').map_or(0, |i| i + 1);
+    // the definition has to begin its line
+    if !input[line_start..span.start()].trim().is_empty() {
+        return None;
+    }
+
+    let mut start = None;
+    let mut lines = vec![];
+    while line_start > 0 {
+        let end = line_start - 1;
+        line_start = input[..end].rfind('\n').map_or(0, |i| i + 1);
+        let line = &input[line_start..end];
+        match line.trim().strip_prefix("///") {
+            Some(text) if !text.starts_with('/') => {
+                start = Some(line_start + line.len() - line.trim_start().len());
+                lines.push(text.strip_prefix(' ').unwrap_or(text));
+            },
+            _ => break
+        }
+    }
+
+    lines.reverse();
+    start.map(|start| (start, lines.join("\n")))
 }
 
 fn function_def(pair: Pair<Rule>) -> FunctionDef {
-    let mut pairs = pair.into_inner();
+    let doc = doc_comment(&pair).map(|(_, doc)| doc);
+    let pairs = pair.into_inner();
+    let name = pairs
+        .clone()
+        .find(|pair| pair.as_rule() == Rule::name)
+        .map(name)
+        .unwrap();
     FunctionDef(
-        name(pairs.next().unwrap()),
-        Function(
-            args(pairs.next().unwrap()),
-            suite(pairs.next().unwrap())
-        )
+        name,
+        function(pairs),
+        doc
     )
 }
 
-fn class_def(mut pairs: Pairs<Rule>) -> Expr {
+fn class_def(pair: Pair<Rule>) -> Expr {
+    let doc = doc_comment(&pair).map(|(_, doc)| doc);
+    let mut pairs = pair.into_inner();
     Expr::StructDef(
         StructDef(
             name(pairs.next().unwrap()),
-            pairs.into_iter()
+            pairs
                 .map(function_def)
-                .collect::<Vec<FunctionDef>>()
+                .collect::<Vec<FunctionDef>>(),
+            doc
+        )
+    )
+}
+
+fn variant(pair: Pair<Rule>) -> Variant {
+    let mut pairs = pair.into_inner();
+    Variant(
+        identifier(pairs.next().unwrap()),
+        pairs.map(identifier).collect()
+    )
+}
+
+fn enum_def(mut pairs: Pairs<Rule>) -> Expr {
+    Expr::EnumDef(
+        EnumDef(
+            name(pairs.next().unwrap()),
+            pairs.map(variant).collect()
         )
     )
 }
 
+/// A decorated definition is parsed as the definition, followed by the
+/// assignment of its name to the result of applying each of its decorators
+/// to it, starting from the one closest to the definition. Its doc comment
+/// is the one before its first decorator, followed by any directly before
+/// the definition itself.
+fn decorated(pair: Pair<Rule>) -> Vec<Expr> {
+    let doc = doc_comment(&pair).map(|(_, doc)| doc);
+    let doc = |own: Option<String>| match (doc.clone(), own) {
+        (Some(doc), Some(own)) => Some(format!("{}\n{}", doc, own)),
+        (doc, own) => doc.or(own),
+    };
+    let mut decorators = vec![];
+    let mut definition = None;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::decorator => {
+                let pair = pair.into_inner().next().unwrap();
+                decorators.push(match pair.as_rule() {
+                    Rule::postfix => postfix(pair.into_inner()),
+                    _ => Value::Name(name(pair)),
+                });
+            },
+            Rule::function_def => {
+                let FunctionDef(name, function, own) = function_def(pair);
+                definition = Some((name.clone(), Expr::FunctionDef(FunctionDef(name, function, doc(own)))));
+            },
+            Rule::class_def => match class_def(pair) {
+                Expr::StructDef(StructDef(name, methods, own)) => {
+                    definition = Some((name.clone(), Expr::StructDef(StructDef(name, methods, doc(own)))));
+                },
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        }
+    }
+
+    let (name, definition) = definition.unwrap();
+    let decorated = decorators
+        .into_iter()
+        .rev()
+        .fold(Value::Name(name.clone()), |value, decorator| Value::FnCall(FnCall(
+            Box::new(decorator),
+            vec![Argument::Value(value)]
+        )));
+    vec![definition, Expr::Assignment(name, decorated)]
+}
+
+/// The statements an `expr` is parsed as, which are more than one for a decorated definition
+fn statements(pair: Pair<Rule>) -> Vec<Expr> {
+    let inner = pair.clone().into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::decorated => decorated(inner),
+        _ => vec![expr(pair)],
+    }
+}
+
 fn while_loop(mut pairs: Pairs<Rule>) -> Expr {
     Expr::WhileLoop(
         value(pairs.next().unwrap()),
@@ -155,9 +491,117 @@ fn if_then_else(mut pairs: Pairs<Rule>) -> Expr {
         value(pairs.next().unwrap()),
         suite(pairs.next().unwrap()),
         pairs.next()
-            .and_then(|s| Some(suite(s)))
-            .or_else(|| Some(Suite(vec![])))
-            .unwrap()
+            .map(suite)
+            .unwrap_or_else(|| Suite(vec![]))
+    )
+}
+
+
+fn try_catch(pairs: Pairs<Rule>) -> Expr {
+    let mut body = Suite(vec![]);
+    let mut handler = None;
+    let mut finally = Suite(vec![]);
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::suite => body = suite(pair),
+            Rule::catch => {
+                let mut pairs = pair.into_inner();
+                handler = Some((
+                    identifier(pairs.next().unwrap()),
+                    suite(pairs.next().unwrap())
+                ));
+            },
+            Rule::finally => finally = suite(pair.into_inner().next().unwrap()),
+            _ => unreachable!()
+        }
+    }
+    Expr::TryCatch(body, handler, finally)
+}
+
+fn assert(mut pairs: Pairs<Rule>) -> Expr {
+    Expr::Assert(
+        value(pairs.next().unwrap()),
+        pairs.next().map(value)
+    )
+}
+
+fn test_block(mut pairs: Pairs<Rule>) -> Expr {
+    let name = pairs.next().unwrap().as_str();
+    Expr::Test(
+        String::from(&name[1..name.len()-1]),
+        suite(pairs.next().unwrap())
+    )
+}
+
+fn with_block(mut pairs: Pairs<Rule>) -> Expr {
+    Expr::With(
+        value(pairs.next().unwrap()),
+        identifier(pairs.next().unwrap()),
+        suite(pairs.next().unwrap())
+    )
+}
+
+fn pattern(pair: Pair<Rule>) -> Pattern {
+    let pair = pair.into_inner().next().unwrap();
+    match pair.as_rule() {
+        Rule::list_pattern => Pattern::List(pair.into_inner().map(pattern).collect()),
+        Rule::variant_pattern => {
+            let mut pairs = pair.into_inner();
+            Pattern::Variant(name(pairs.next().unwrap()), pairs.map(pattern).collect())
+        },
+        Rule::literal => Pattern::Literal(literal(pair)),
+        Rule::name => match name(pair) {
+            Name::Name(Identifier(n)) if n == "_" => Pattern::Wildcard,
+            Name::Name(n) => Pattern::Binding(n),
+            otherwise => Pattern::Value(otherwise),
+        },
+        _ => unreachable!()
+    }
+}
+
+fn arm(pair: Pair<Rule>) -> Arm {
+    let mut patterns = vec![];
+    let mut body = Suite(vec![]);
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::pattern => patterns.push(pattern(pair)),
+            Rule::suite => body = suite(pair),
+            Rule::value => body = Suite(vec![Expr::Value(value(pair))]),
+            _ => unreachable!()
+        }
+    }
+    Arm(patterns, body)
+}
+
+fn match_expr(mut pairs: Pairs<Rule>) -> Expr {
+    Expr::Match(
+        value(pairs.next().unwrap()),
+        pairs.map(arm).collect()
+    )
+}
+
+fn module_path(pair: Pair<Rule>) -> String {
+    let pair = pair.into_inner().next().unwrap();
+    match pair.as_rule() {
+        Rule::string_literal => {
+            let result = pair.as_str().to_string();
+            String::from(&result[1..result.len()-1])
+        },
+        Rule::identifier => identifier(pair).0,
+        _ => unreachable!()
+    }
+}
+
+fn import(mut pairs: Pairs<Rule>) -> Expr {
+    Expr::Import(module_path(pairs.next().unwrap()))
+}
+
+fn from_import(mut pairs: Pairs<Rule>) -> Expr {
+    Expr::FromImport(
+        module_path(pairs.next().unwrap()),
+        pairs
+            .map(identifier)
+            .collect::<Vec<Identifier>>()
     )
 }
 
@@ -165,11 +609,26 @@ fn if_then_else(mut pairs: Pairs<Rule>) -> Expr {
 fn expr(pair: Pair<Rule>) -> Expr {
     let pair = pair.into_inner().next().unwrap();
     match pair.as_rule() {
+        Rule::import => import(pair.into_inner()),
+        Rule::from_import => from_import(pair.into_inner()),
+        Rule::destructuring => destructuring(pair.into_inner()),
         Rule::assignment => assignment(pair.into_inner()),
-        Rule::class_def => class_def(pair.into_inner()),
+        Rule::declaration => declaration(pair.into_inner()),
+        Rule::class_def => class_def(pair),
+        Rule::enum_def => enum_def(pair.into_inner()),
         Rule::function_def => Expr::FunctionDef(function_def(pair)),
         Rule::while_loop => while_loop(pair.into_inner()),
         Rule::if_then_else => if_then_else(pair.into_inner()),
+        Rule::try_catch => try_catch(pair.into_inner()),
+        Rule::global_declaration => Expr::Global(pair.into_inner().map(identifier).collect()),
+        Rule::nonlocal_declaration => Expr::Nonlocal(pair.into_inner().map(identifier).collect()),
+        Rule::with_block => with_block(pair.into_inner()),
+        Rule::defer => Expr::Defer(value(pair.into_inner().next().unwrap())),
+        Rule::assert => assert(pair.into_inner()),
+        Rule::test_block => test_block(pair.into_inner()),
+        Rule::throw => Expr::Throw(value(pair.into_inner().next().unwrap())),
+        Rule::match_expr => match_expr(pair.into_inner()),
+        Rule::yield_value => Expr::Yield(value(pair.into_inner().next().unwrap())),
         Rule::value => Expr::Value(value(pair)),
         Rule::expr => expr(pair),
         _ => unreachable!()
@@ -190,10 +649,7 @@ pub fn parse(input: &str) -> Result<Suite, String> {
     for pair in pairs {
         match pair.as_rule() {
             Rule::expr => {
-                let token = expr(pair.clone());
-                let compiled = Compile::<Rust>::compile(token.clone()).unwrap();
-                // println!("{:#?}\n\t=>\n{}", token, compiled);
-                result.push(token);
+                result.extend(statements(pair));
             },
             Rule::COMMENT => {}
             Rule::EOI => {}
@@ -0,0 +1,726 @@
+use crate::parser::{Rule, Xasm};
+use crate::{
+    Argument, Arm, Clause, Comprehension, Destructure, Element, EnumDef, Entry, Expr, FnCall, Function, FunctionDef,
+    Identifier, Literal, Name, Parameter, Pattern, Slice, StructDef, Suite, Type, Value, Variant,
+};
+
+use pest::Parser;
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::collections::BTreeSet;
+use core::cell::{Cell, RefCell};
+use core::fmt;
+
+
+/// Prints syntax trees as xasm source. Any program the parser produces
+/// prints as source that parses back to the same program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Printer {
+    /// The text each level of indentation is made of, such as four spaces or a tab
+    pub indent: String,
+    /// How long a line can be before the items of the call, list, dictionary
+    /// or parameter list on it are put on lines of their own
+    pub width: usize,
+    /// What the source said about each statement, method, arm, variant and
+    /// item between brackets, in the order they are printed
+    trivia: Vec<Trivia>,
+    /// The number of statements, methods, arms, variants, and items of
+    /// brackets printed so far
+    items: Cell<usize>,
+    /// The number of lists of items between brackets printed so far
+    lists: Cell<usize>,
+    /// The lists that fit the width by themselves, but not after the text
+    /// before them on their line, so they are put on lines of their own
+    broken: RefCell<BTreeSet<usize>>,
+}
+
+/// A list printed on one line is marked with these, so that the line it is
+/// on can be measured once the whole statement has been printed. The start
+/// of the list is followed by its number.
+const LIST_START: char = '\u{E000}';
+const LIST_NUMBER_END: char = '\u{E001}';
+const LIST_END: char = '\u{E002}';
+
+/// What the source of a statement, method, match arm, enum variant or item
+/// between brackets said besides its syntax tree, which the formatter passes along to the printer
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Trivia {
+    /// Whether a blank line came before it
+    pub blank_line: bool,
+    /// The comments on the lines before it, with an empty comment for each
+    /// blank line between them
+    pub leading: Vec<String>,
+    /// The comments at the end of its last line
+    pub trailing: Vec<String>,
+    /// The comments on the lines after it, at the end of a block
+    pub after: Vec<String>,
+    /// How many decorators it was written with, and whether they decorate a class
+    pub decorators: Option<(usize, bool)>,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new("    ")
+    }
+}
+
+impl Printer {
+    pub fn new(indent: &str) -> Self {
+        Self {
+            indent: indent.to_string(),
+            width: 100,
+            trivia: vec![],
+            items: Cell::new(0),
+            lists: Cell::new(0),
+            broken: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    /// Prints each statement of a program on a line of its own
+    pub fn print(&self, suite: &Suite) -> String {
+        self.items.set(0);
+        self.lists.set(0);
+        self.broken.borrow_mut().clear();
+        unmarked(&self.statements(suite, 0))
+    }
+
+    /// Prints a program along with the trivia of each of its items, failing
+    /// if the trivia doesn't line up with the items printed
+    pub(crate) fn print_with(&self, suite: &Suite, trivia: Vec<Trivia>) -> Option<String> {
+        let count = trivia.len();
+        let printer = Self { trivia, ..self.clone() };
+        let result = printer.print(suite);
+        Some(result).filter(|_| printer.items.get() == count)
+    }
+
+    /// The trivia of the next statement, method, arm, variant or item to be printed
+    fn next_trivia(&self) -> Option<&Trivia> {
+        let index = self.items.get();
+        self.items.set(index + 1);
+        self.trivia.get(index)
+    }
+
+    fn statements(&self, suite: &Suite, depth: usize) -> String {
+        let Suite(exprs) = suite;
+        let mut lines = vec![];
+        let mut values = vec![];
+        let mut i = 0;
+        while i < exprs.len() {
+            let trivia = self.next_trivia();
+            let decorators = trivia
+                .and_then(|trivia| trivia.decorators)
+                .and_then(|(count, class)| decorators(&exprs[i], exprs.get(i + 1), count, class));
+            let line = self.fit(depth, || match &decorators {
+                Some(decorators) => self.decorated(&exprs[i], decorators, depth),
+                None => self.expr(&exprs[i], depth),
+            });
+            lines.push((trivia, line));
+            values.push(decorators.is_none() && ends_with_value(&exprs[i]));
+            i += if decorators.is_some() { 2 } else { 1 };
+        }
+        for i in 0..lines.len() {
+            // otherwise the value the statement ends with would be continued by the next line
+            let continues = lines.get(i + 1).is_some_and(|(_, next)| unmarked(next).starts_with(['(', '[', '-']));
+            if continues && values[i] {
+                lines[i].1.push(';');
+            }
+        }
+        self.layout(lines, depth)
+    }
+
+    /// Puts each item on a line of its own, along with its comments
+    fn layout(&self, items: Vec<(Option<&Trivia>, String)>, depth: usize) -> String {
+        let indent = self.indent.repeat(depth);
+        let mut result = String::new();
+        for (i, (trivia, item)) in items.into_iter().enumerate() {
+            let trivia = trivia.cloned().unwrap_or_default();
+            if trivia.blank_line && i > 0 {
+                result.push('\n');
+            }
+            for comment in trivia.leading {
+                // an empty comment stands for a blank line between comments
+                if comment.is_empty() {
+                    result.push('\n');
+                } else {
+                    result += &format!("{}{}\n", indent, comment);
+                }
+            }
+            result += &format!("{}{}", indent, item);
+            for comment in trivia.trailing {
+                result += &format!(" {}", comment);
+            }
+            result.push('\n');
+            for comment in trivia.after {
+                result += &format!("{}{}\n", indent, comment);
+            }
+        }
+        result
+    }
+
+    fn block(&self, suite: &Suite, depth: usize) -> String {
+        if suite.0.is_empty() {
+            return "{}".to_string();
+        }
+        format!("{{\n{}{}}}", self.statements(suite, depth + 1), self.indent.repeat(depth))
+    }
+
+    /// Puts each item between braces, indented one level deeper than the braces
+    fn lines(&self, items: Vec<(Option<&Trivia>, String)>, depth: usize) -> String {
+        if items.is_empty() {
+            return "{}".to_string();
+        }
+        format!("{{\n{}{}}}", self.layout(items, depth + 1), self.indent.repeat(depth))
+    }
+
+    /// Prints something that begins a line, such as a statement, breaking
+    /// the outermost list on each line longer than the width, including the
+    /// text before the list, until every line fits or has no list to break
+    fn fit(&self, depth: usize, print: impl Fn() -> String) -> String {
+        let (items, lists) = (self.items.get(), self.lists.get());
+        loop {
+            let text = print();
+            match self.overflowing(&text, self.indent.len() * depth) {
+                Some(list) => {
+                    self.broken.borrow_mut().insert(list);
+                    self.items.set(items);
+                    self.lists.set(lists);
+                }
+                None => return text,
+            }
+        }
+    }
+
+    /// The outermost list printed on one line that is open where a line of
+    /// `text`, the first of which starts at `column`, gets longer than the width
+    fn overflowing(&self, text: &str, mut column: usize) -> Option<usize> {
+        let mut open = vec![];
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                LIST_START => open.push(chars.by_ref().take_while(|&c| c != LIST_NUMBER_END).collect::<String>()),
+                LIST_END => {
+                    open.pop();
+                }
+                '\n' => column = 0,
+                _ => {
+                    column += 1;
+                    if column > self.width && !open.is_empty() {
+                        return open[0].parse().ok();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Puts items between brackets on one line, or one to a line with a
+    /// trailing comma if that line would be longer than the width, or any
+    /// item has comments, which are kept next to it
+    fn items<T>(&self, open: &str, items: &[T], close: &str, depth: usize, item: impl Fn(&T, usize) -> String) -> String {
+        let list = self.lists.get();
+        self.lists.set(list + 1);
+        let start = self.items.get();
+        let printed = items.iter().map(|each| (self.next_trivia(), item(each, depth))).collect::<Vec<_>>();
+        let commented = printed.iter().any(|(trivia, _)| {
+            trivia.is_some_and(|trivia| !(trivia.leading.is_empty() && trivia.trailing.is_empty() && trivia.after.is_empty()))
+        });
+        let line = printed.into_iter().map(|(_, each)| each).collect::<Vec<String>>().join(", ");
+        let line = format!("{}{}{}", open, line, close);
+        let length = self.indent.len() * depth + unmarked(line.lines().next().unwrap_or_default()).chars().count();
+        if items.is_empty() {
+            return line;
+        }
+        if !commented && length <= self.width && !self.broken.borrow().contains(&list) {
+            return format!("{}{}{}{}{}", LIST_START, list, LIST_NUMBER_END, line, LIST_END);
+        }
+
+        // the items are printed again, so their statements and lists are numbered again
+        self.items.set(start);
+        self.lists.set(list + 1);
+        let lines = items.iter().map(|each| (self.next_trivia(), format!("{},", item(each, depth + 1)))).collect();
+        format!("{}\n{}{}{}", open, self.layout(lines, depth + 1), self.indent.repeat(depth), close)
+    }
+
+    fn methods(&self, methods: &[FunctionDef], depth: usize) -> String {
+        self.lines(
+            methods
+                .iter()
+                .map(|method| (self.next_trivia(), self.fit(depth + 1, || self.function_def(method, depth + 1))))
+                .collect(),
+            depth,
+        )
+    }
+
+    /// Prints a decorated definition the way it was written, with its doc
+    /// comment before its decorators
+    fn decorated(&self, definition: &Expr, decorators: &[&Value], depth: usize) -> String {
+        let indent = self.indent.repeat(depth);
+        let (doc, definition) = match definition {
+            Expr::FunctionDef(FunctionDef(name, function, doc)) => {
+                (doc, Expr::FunctionDef(FunctionDef(name.clone(), function.clone(), None)))
+            }
+            Expr::StructDef(StructDef(name, methods, doc)) => {
+                (doc, Expr::StructDef(StructDef(name.clone(), methods.clone(), None)))
+            }
+            _ => unreachable!("only definitions are decorated"),
+        };
+        let mut lines = decorators
+            .iter()
+            .map(|decorator| format!("@{}", self.value(decorator, depth)))
+            .collect::<Vec<String>>();
+        lines.push(self.expr(&definition, depth));
+        format!("{}{}", self.doc(doc, depth), lines.join(&format!("\n{}", indent)))
+    }
+
+    fn expr(&self, expr: &Expr, depth: usize) -> String {
+        let value = |value| self.value(value, depth);
+        match expr {
+            Expr::Assignment(name, initial) => format!("{} = {}", self.target(name, depth), value(initial)),
+            Expr::Declaration(Identifier(name), Some(Type(Identifier(annotation))), initial) => {
+                format!("let {}: {} = {}", name, annotation, value(initial))
+            }
+            Expr::Declaration(Identifier(name), None, initial) => format!("let {} = {}", name, value(initial)),
+            Expr::WhileLoop(condition, body) => format!("while {} {}", value(condition), self.block(body, depth)),
+            Expr::IfThenElse(condition, then_body, Suite(else_body)) if else_body.is_empty() => {
+                format!("if {} {}", value(condition), self.block(then_body, depth))
+            }
+            Expr::IfThenElse(condition, then_body, else_body) => format!(
+                "if {} {} else {}",
+                value(condition),
+                self.block(then_body, depth),
+                self.block(else_body, depth)
+            ),
+            Expr::FunctionDef(def) => self.function_def(def, depth),
+            Expr::StructDef(StructDef(name, methods, doc)) => format!(
+                "{}class {} {}",
+                self.doc(doc, depth),
+                self.target(name, depth),
+                self.methods(methods, depth)
+            ),
+            Expr::EnumDef(EnumDef(name, variants)) => format!(
+                "enum {} {}",
+                self.target(name, depth),
+                self.lines(
+                    variants.iter().map(|variant| (self.next_trivia(), format!("{},", variant_text(variant)))).collect(),
+                    depth
+                )
+            ),
+            Expr::Import(path) => format!("import {}", module_path(path)),
+            Expr::FromImport(path, names) => format!("from {} import {}", module_path(path), identifiers(names)),
+            Expr::Throw(thrown) => format!("throw {}", value(thrown)),
+            Expr::Assert(condition, None) => format!("assert {}", value(condition)),
+            Expr::Assert(condition, Some(message)) => format!("assert {}, {}", value(condition), value(message)),
+            Expr::Test(name, body) => format!("test \"{}\" {}", name, self.block(body, depth)),
+            Expr::Yield(yielded) => format!("yield {}", value(yielded)),
+            Expr::TryCatch(body, handler, finally) => {
+                let mut result = format!("try {}", self.block(body, depth));
+                if let Some((Identifier(name), handler)) = handler {
+                    result += &format!(" catch {} {}", name, self.block(handler, depth));
+                }
+                // a try block must be followed by at least one of the two
+                if !finally.0.is_empty() || handler.is_none() {
+                    result += &format!(" finally {}", self.block(finally, depth));
+                }
+                result
+            }
+            Expr::Global(names) => format!("global {}", identifiers(names)),
+            Expr::Nonlocal(names) => format!("nonlocal {}", identifiers(names)),
+            Expr::Defer(deferred) => format!("defer {}", value(deferred)),
+            Expr::With(resource, Identifier(name), body) => {
+                format!("with {} as {} {}", value(resource), name, self.block(body, depth))
+            }
+            Expr::Match(subject, arms) => format!(
+                "match {} {}",
+                value(subject),
+                self.lines(
+                    arms.iter().map(|arm| (self.next_trivia(), self.fit(depth + 1, || self.arm(arm, depth + 1)))).collect(),
+                    depth
+                )
+            ),
+            Expr::Destructure(targets, values) => format!(
+                "{} = {}",
+                targets.iter().map(|target| self.destructure(target, depth)).collect::<Vec<String>>().join(", "),
+                values.iter().map(value).collect::<Vec<String>>().join(", ")
+            ),
+            // a statement beginning with `if` is an if statement rather than a conditional value
+            Expr::Value(result) if begins_with_conditional(result) => format!("({})", value(result)),
+            Expr::Value(result) => value(result),
+        }
+    }
+
+    fn function_def(&self, def: &FunctionDef, depth: usize) -> String {
+        let FunctionDef(_, Function(_, body, _, _), doc) = def;
+        format!("{}{} {}", self.doc(doc, depth), self.header(def, depth), self.block(body, depth))
+    }
+
+    /// Prints the line a function definition begins with, such as
+    /// `async fn get(url, retries = 3) -> str`, without its body or doc comment
+    pub fn signature(&self, def: &FunctionDef) -> String {
+        unmarked(&self.fit(0, || self.header(def, 0)))
+    }
+
+    fn header(&self, def: &FunctionDef, depth: usize) -> String {
+        let FunctionDef(name, Function(parameters, _, returns, asynchronous), _) = def;
+        format!(
+            "{}fn {}{}{}",
+            if *asynchronous { "async " } else { "" },
+            self.target(name, depth),
+            self.items("(", parameters, ")", depth, |parameter, depth| self.parameter(parameter, depth)),
+            return_type(returns)
+        )
+    }
+
+    /// Prints a doc comment as the lines before a definition, each starting with `///`
+    fn doc(&self, doc: &Option<String>, depth: usize) -> String {
+        let indent = self.indent.repeat(depth);
+        doc.iter()
+            .flat_map(|doc| doc.split('\n'))
+            .map(|line| match line {
+                "" => format!("///\n{}", indent),
+                _ => format!("/// {}\n{}", line, indent),
+            })
+            .collect()
+    }
+
+    fn arm(&self, arm: &Arm, depth: usize) -> String {
+        let Arm(patterns, body) = arm;
+        let patterns = patterns.iter().map(|pattern| self.pattern(pattern, depth)).collect::<Vec<String>>().join(" | ");
+        match &body.0[..] {
+            // a value in braces would be read as a block
+            [Expr::Value(result)] if !begins_with_dict(result) => format!("{} => {},", patterns, self.value(result, depth)),
+            _ => format!("{} => {}", patterns, self.block(body, depth)),
+        }
+    }
+
+    fn pattern(&self, pattern: &Pattern, depth: usize) -> String {
+        match pattern {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Binding(Identifier(name)) => name.clone(),
+            Pattern::Literal(literal) => literal_text(literal),
+            Pattern::Value(name) => self.target(name, depth),
+            Pattern::List(patterns) => format!("[{}]", self.patterns(patterns, depth)),
+            Pattern::Variant(name, patterns) => {
+                format!("{}({})", self.target(name, depth), self.patterns(patterns, depth))
+            }
+        }
+    }
+
+    fn patterns(&self, patterns: &[Pattern], depth: usize) -> String {
+        patterns.iter().map(|pattern| self.pattern(pattern, depth)).collect::<Vec<String>>().join(", ")
+    }
+
+    fn destructure(&self, target: &Destructure, depth: usize) -> String {
+        match target {
+            Destructure::Name(name) => self.target(name, depth),
+            Destructure::List(targets) => format!(
+                "[{}]",
+                targets.iter().map(|target| self.destructure(target, depth)).collect::<Vec<String>>().join(", ")
+            ),
+            Destructure::Dict(names) => format!("{{{}}}", identifiers(names)),
+        }
+    }
+
+    fn parameter(&self, parameter: &Parameter, depth: usize) -> String {
+        match parameter {
+            Parameter::Name(Identifier(name)) => name.clone(),
+            Parameter::Default(Identifier(name), default) => format!("{} = {}", name, self.value(default, depth)),
+            Parameter::Rest(Identifier(name)) => format!("...{}", name),
+            Parameter::Pattern(target) => self.destructure(target, depth),
+            Parameter::Typed(parameter, Type(Identifier(annotation))) => match &**parameter {
+                Parameter::Default(Identifier(name), default) => {
+                    format!("{}: {} = {}", name, annotation, self.value(default, depth))
+                }
+                parameter => format!("{}: {}", self.parameter(parameter, depth), annotation),
+            },
+        }
+    }
+
+    /// A name that is assigned to, which can only begin with an identifier or a group
+    fn target(&self, name: &Name, depth: usize) -> String {
+        self.name(name, depth, |_| false)
+    }
+
+    /// Prints a name, putting its head in parentheses unless it is a name or `bare` allows it
+    fn name(&self, name: &Name, depth: usize, bare: fn(&Value) -> bool) -> String {
+        let head = |head: &Value| match head {
+            Value::Name(name) => self.name(name, depth, bare),
+            head if bare(head) => self.value(head, depth),
+            head => format!("({})", self.value(head, depth)),
+        };
+        match name {
+            Name::Name(Identifier(name)) => name.clone(),
+            Name::DotName(value, members) => format!(
+                "{}.{}",
+                head(value),
+                members.iter().map(|Identifier(member)| member.as_str()).collect::<Vec<&str>>().join(".")
+            ),
+            Name::IndexName(value, indices) => format!(
+                "{}{}",
+                head(value),
+                indices.iter().map(|index| format!("[{}]", self.value(index, depth))).collect::<String>()
+            ),
+        }
+    }
+
+    /// The value a member access, index, slice or call applies to
+    fn head(&self, value: &Value, depth: usize) -> String {
+        match value {
+            Value::Name(_) | Value::FnCall(_) | Value::Slice(_, _) => self.value(value, depth),
+            value => format!("({})", self.value(value, depth)),
+        }
+    }
+
+    /// An operand of an operator or of `await`, which is put in parentheses if
+    /// it is an operation that `group` says would otherwise be split apart
+    fn operand(&self, value: &Value, depth: usize, group: impl Fn(usize) -> bool) -> String {
+        match value {
+            Value::Operation(_, operator, _) if group(operator.precedence()) => format!("({})", self.value(value, depth)),
+            // the body of a lambda would take in the rest of the operation
+            Value::Function(function) if is_lambda(function) => format!("({})", self.value(value, depth)),
+            value => self.value(value, depth),
+        }
+    }
+
+    fn value(&self, value: &Value, depth: usize) -> String {
+        match value {
+            Value::Name(name) => self.name(name, depth, |head| matches!(head, Value::FnCall(_) | Value::Slice(_, _))),
+            Value::Literal(literal) => literal_text(literal),
+            Value::FnCall(FnCall(function, arguments)) => format!(
+                "{}{}",
+                self.head(function, depth),
+                self.items("(", arguments, ")", depth, |argument, depth| self.argument(argument, depth))
+            ),
+            Value::Function(function) => self.function(function, depth),
+            Value::Conditional(condition, then_value, else_value) => format!(
+                "if {} {{ {} }} else {}",
+                self.value(condition, depth),
+                self.value(then_value, depth),
+                match &**else_value {
+                    Value::Conditional(_, _, _) => self.value(else_value, depth),
+                    else_value => format!("{{ {} }}", self.value(else_value, depth)),
+                }
+            ),
+            Value::Slice(sliced, Slice(start, end, step)) => {
+                let bound = |bound: &Option<Box<Value>>| bound.as_ref().map(|bound| self.value(bound, depth)).unwrap_or_default();
+                let step = if step.is_some() { format!(":{}", bound(step)) } else { String::new() };
+                format!("{}[{}:{}{}]", self.head(sliced, depth), bound(start), bound(end), step)
+            }
+            Value::List(elements) => self.items("[", elements, "]", depth, |element, depth| self.element(element, depth)),
+            Value::Dict(entries) => self.items("{", entries, "}", depth, |entry, depth| self.entry(entry, depth)),
+            Value::Comprehension(comprehension) => match &**comprehension {
+                Comprehension::List(element, clauses) => {
+                    format!("[{}{}]", self.value(element, depth), self.clauses(clauses, depth))
+                }
+                Comprehension::Dict(key, value, clauses) => format!(
+                    "{{{}: {}{}}}",
+                    self.value(key, depth),
+                    self.value(value, depth),
+                    self.clauses(clauses, depth)
+                ),
+            },
+            // operators of equal precedence apply from the left
+            Value::Operation(left, operator, right) => format!(
+                "{} {} {}",
+                self.operand(left, depth, |precedence| precedence < operator.precedence()),
+                operator.symbol(),
+                self.operand(right, depth, |precedence| precedence <= operator.precedence())
+            ),
+            Value::Await(value) => format!("await {}", self.operand(value, depth, |_| true)),
+        }
+    }
+
+    /// Prints a function whose body is a single value as a lambda
+    fn function(&self, function: &Function, depth: usize) -> String {
+        let Function(parameters, body, returns, asynchronous) = function;
+        let prefix = if *asynchronous { "async " } else { "" };
+        let arguments = || self.items("(", parameters, ")", depth, |parameter, depth| self.parameter(parameter, depth));
+        match &body.0[..] {
+            [Expr::Value(result)] if !asynchronous && returns.is_none() => {
+                format!(
+                    "{} {}",
+                    self.items("|", parameters, "|", depth, |parameter, depth| self.parameter(parameter, depth)),
+                    self.value(result, depth)
+                )
+            }
+            [Expr::Value(result)] => format!(
+                "{}fn{}{} => {}",
+                prefix,
+                arguments(),
+                return_type(returns),
+                self.value(result, depth)
+            ),
+            _ => format!("{}fn{}{} {}", prefix, arguments(), return_type(returns), self.block(body, depth)),
+        }
+    }
+
+    fn argument(&self, argument: &Argument, depth: usize) -> String {
+        match argument {
+            Argument::Value(value) => self.value(value, depth),
+            Argument::Spread(value) => format!("...{}", self.value(value, depth)),
+            Argument::Keyword(Identifier(name), value) => format!("{} = {}", name, self.value(value, depth)),
+        }
+    }
+
+    fn element(&self, element: &Element, depth: usize) -> String {
+        match element {
+            Element::Value(value) => self.value(value, depth),
+            Element::Spread(value) => format!("...{}", self.value(value, depth)),
+        }
+    }
+
+    fn entry(&self, entry: &Entry, depth: usize) -> String {
+        match entry {
+            Entry::Pair(key, value) => format!("{}: {}", self.value(key, depth), self.value(value, depth)),
+            Entry::Spread(value) => format!("...{}", self.value(value, depth)),
+        }
+    }
+
+    fn clauses(&self, clauses: &[Clause], depth: usize) -> String {
+        clauses
+            .iter()
+            .map(|clause| match clause {
+                Clause::For(target, items) => {
+                    format!(" for {} in {}", self.destructure(target, depth), self.value(items, depth))
+                }
+                Clause::If(condition) => format!(" if {}", self.value(condition, depth)),
+            })
+            .collect()
+    }
+}
+
+
+/// Text without the marks put around the lists printed on one line
+fn unmarked(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            LIST_START => chars.by_ref().take_while(|&c| c != LIST_NUMBER_END).for_each(drop),
+            LIST_END => {}
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn is_lambda(function: &Function) -> bool {
+    matches!(&(function.1).0[..], [Expr::Value(_)])
+}
+
+fn begins_with_dict(value: &Value) -> bool {
+    match value {
+        Value::Dict(_) => true,
+        Value::Comprehension(comprehension) => matches!(**comprehension, Comprehension::Dict(_, _, _)),
+        Value::Operation(left, _, _) => begins_with_dict(left),
+        _ => false,
+    }
+}
+
+fn begins_with_conditional(value: &Value) -> bool {
+    match value {
+        Value::Conditional(_, _, _) => true,
+        Value::Operation(left, _, _) => begins_with_conditional(left),
+        _ => false,
+    }
+}
+
+/// Whether a statement ends with a value that the next line could continue
+/// The decorators a definition followed by the assignment the parser gives
+/// a decorated definition applies, outermost first, if the two still have
+/// the shape the parser gave them
+fn decorators<'a>(definition: &Expr, assignment: Option<&'a Expr>, count: usize, class: bool) -> Option<Vec<&'a Value>> {
+    let defined = match definition {
+        Expr::FunctionDef(FunctionDef(name, _, _)) if !class => name,
+        Expr::StructDef(StructDef(name, _, _)) if class => name,
+        _ => return None,
+    };
+    let mut value = match assignment {
+        Some(Expr::Assignment(name, value)) if name == defined => value,
+        _ => return None,
+    };
+    let mut decorators = vec![];
+    for _ in 0..count {
+        match value {
+            Value::FnCall(FnCall(decorator, arguments)) => match &arguments[..] {
+                [Argument::Value(inner)] => {
+                    decorators.push(&**decorator);
+                    value = inner;
+                }
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+    Some(decorators).filter(|_| *value == Value::Name(defined.clone()))
+}
+
+fn ends_with_value(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Assignment(_, _)
+            | Expr::Declaration(_, _, _)
+            | Expr::Destructure(_, _)
+            | Expr::Throw(_)
+            | Expr::Yield(_)
+            | Expr::Defer(_)
+            | Expr::Assert(_, _)
+            | Expr::Value(_)
+    )
+}
+
+fn return_type(returns: &Option<Type>) -> String {
+    match returns {
+        Some(Type(Identifier(name))) => format!(" -> {}", name),
+        None => String::new(),
+    }
+}
+
+fn identifiers(names: &[Identifier]) -> String {
+    names.iter().map(|Identifier(name)| name.as_str()).collect::<Vec<&str>>().join(", ")
+}
+
+fn variant_text(variant: &Variant) -> String {
+    match variant {
+        Variant(Identifier(name), fields) if fields.is_empty() => name.clone(),
+        Variant(Identifier(name), fields) => format!("{}({})", name, identifiers(fields)),
+    }
+}
+
+fn literal_text(literal: &Literal) -> String {
+    match literal {
+        Literal::String(string) => format!("\"{}\"", string),
+        Literal::Number(number) => number.clone(),
+        Literal::ForeignFunction(name) => format!("@{}", name),
+    }
+}
+
+/// A module path is written as an identifier when it is one, and as a string otherwise
+fn module_path(path: &str) -> String {
+    match Xasm::parse(Rule::identifier, path) {
+        Ok(pairs) if pairs.as_str() == path => path.to_string(),
+        _ => format!("\"{}\"", path),
+    }
+}
+
+
+impl fmt::Display for Suite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Printer::default().print(self))
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let printer = Printer::default();
+        f.write_str(&unmarked(&printer.fit(0, || printer.expr(self, 0))))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let printer = Printer::default();
+        f.write_str(&unmarked(&printer.fit(0, || printer.value(self, 0))))
+    }
+}
@@ -1,5 +1,5 @@
 
-use crate::{Target, Compile, MACHINE_NAME, Value, Identifier};
+use crate::{keyword_names, keyword_values, Target, MACHINE_NAME, Identifier};
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -19,6 +19,10 @@ impl Target for Rust {
         )
     }
 
+    fn builtin(name: impl ToString) -> String {
+        format!("{}.builtin({})?;", MACHINE_NAME, Self::quote(name))
+    }
+
     fn number(number: impl ToString) -> String {
         format!("Value::number({})", number.to_string())
     }
@@ -27,6 +31,11 @@ impl Target for Rust {
         format!("Value::string({})", string.to_string())
     }
 
+    fn names(names: Vec<String>) -> String {
+        let names = names.iter().map(Self::quote).collect::<Vec<String>>();
+        format!("&[{}]", names.join(", "))
+    }
+
     fn store(value: impl ToString) -> String {
         format!("{}{}.store();", value.to_string(), MACHINE_NAME)
     }
@@ -37,12 +46,40 @@ impl Target for Rust {
 
     fn func(body: impl ToString) -> String {
         format!(
-            "Value::function(|{MACHINE}: &mut Machine| {{{func}}}, &{MACHINE})",
+            "Value::function(|{MACHINE}: &mut Machine| -> Result<(), Value> {{{func}Ok(())}}, &{MACHINE})",
+            func = body.to_string(),
+            MACHINE = MACHINE_NAME
+        )
+    }
+
+    fn generator(body: impl ToString) -> String {
+        format!(
+            "Value::generator(|{MACHINE}: &mut Machine| -> Result<(), Value> {{{func}Ok(())}}, &{MACHINE})",
+            func = body.to_string(),
+            MACHINE = MACHINE_NAME
+        )
+    }
+
+    fn async_func(body: impl ToString) -> String {
+        format!(
+            "Value::future(|{MACHINE}: &mut Machine| -> Result<(), Value> {{{func}Ok(())}}, &{MACHINE})",
             func = body.to_string(),
             MACHINE = MACHINE_NAME
         )
     }
 
+    fn capture(variables: Vec<String>) -> String {
+        format!("{}.capture({});", MACHINE_NAME, Self::names(variables))
+    }
+
+    fn global(names: Vec<String>) -> String {
+        format!("{}.global({});", MACHINE_NAME, Self::names(names))
+    }
+
+    fn nonlocal(names: Vec<String>) -> String {
+        format!("{}.nonlocal({});", MACHINE_NAME, Self::names(names))
+    }
+
     fn foreign_func(name: impl ToString) -> String {
         format!(
             "Value::function({name}, &{MACHINE})",
@@ -53,7 +90,7 @@ impl Target for Rust {
 
     fn while_loop(condition: impl ToString, body: impl ToString) -> String {
         format!(
-            "{body}{condition}{MACHINE}.while_loop();",
+            "{body}{condition}{MACHINE}.while_loop()?;",
             MACHINE = MACHINE_NAME,
             condition = Self::push(Self::func(condition)),
             body = Self::push(Self::func(body))
@@ -66,7 +103,7 @@ impl Target for Rust {
         else_fn: impl ToString,
     ) -> String {
         format!(
-            "{else_fn}{then_fn}{condition}{MACHINE}.if_then_else();",
+            "{else_fn}{then_fn}{condition}{MACHINE}.if_then_else()?;",
             MACHINE = MACHINE_NAME,
             condition = Self::push(Self::func(condition)),
             then_fn = Self::push(Self::func(then_fn)),
@@ -74,22 +111,99 @@ impl Target for Rust {
         )
     }
 
-    fn call(func: impl ToString) -> String {
+    fn call(func: impl ToString, argc: impl ToString, keywords: Vec<(String, String)>) -> String {
+        if keywords.is_empty() {
+            format!(
+                "{argc}{func}{MACHINE}.call()?;",
+                MACHINE = MACHINE_NAME,
+                argc = argc.to_string(),
+                func = func.to_string()
+            )
+        } else {
+            format!(
+                "{argc}{values}{func}{MACHINE}.keyword_call({keywords})?;",
+                MACHINE = MACHINE_NAME,
+                argc = argc.to_string(),
+                values = keyword_values(&keywords),
+                func = func.to_string(),
+                keywords = keyword_names::<Self>(&keywords)
+            )
+        }
+    }
+
+    fn method_call(
+        method_name: impl ToString,
+        argc: impl ToString,
+        keywords: Vec<(String, String)>,
+    ) -> String {
+        if keywords.is_empty() {
+            format!(
+                "{argc}{method_name}{MACHINE}.method_call()?;",
+                argc = argc.to_string(),
+                method_name = Self::push(method_name.to_string()),
+                MACHINE = MACHINE_NAME
+            )
+        } else {
+            format!(
+                "{argc}{values}{method_name}{MACHINE}.keyword_method_call({keywords})?;",
+                argc = argc.to_string(),
+                values = keyword_values(&keywords),
+                method_name = Self::push(method_name.to_string()),
+                keywords = keyword_names::<Self>(&keywords),
+                MACHINE = MACHINE_NAME
+            )
+        }
+    }
+
+    fn arguments(parameters: Vec<String>, defaults: Vec<String>, variadic: bool) -> String {
         format!(
-            "{func}{MACHINE}.call();",
-            MACHINE = MACHINE_NAME,
-            func = func.to_string()
+            "{defaults}{MACHINE}.arguments({parameters}, {count}, {variadic})?;",
+            defaults = defaults
+                .iter()
+                .rev()
+                .map(|default| Self::push(Self::func(default)))
+                .collect::<String>(),
+            parameters = Self::names(parameters),
+            count = defaults.len(),
+            variadic = variadic,
+            MACHINE = MACHINE_NAME
         )
     }
 
-    fn method_call(method_name: impl ToString) -> String {
+    fn argument(value: impl ToString) -> String {
+        format!("{}{}.argument();", value.to_string(), MACHINE_NAME)
+    }
+
+    fn spread(list: impl ToString) -> String {
+        format!("{}{}.spread()?;", list.to_string(), MACHINE_NAME)
+    }
+
+    fn throw(value: impl ToString) -> String {
         format!(
-            "{method_name}{MACHINE}.method_call();",
-            method_name = Self::push(method_name.to_string()),
+            "{value}Err({MACHINE}.throw())?;",
+            value = value.to_string(),
             MACHINE = MACHINE_NAME
         )
     }
 
+    fn try_catch(body: impl ToString, handler: impl ToString, finally: impl ToString) -> String {
+        format!(
+            "{finally}{handler}{body}{MACHINE}.try_catch()?;",
+            MACHINE = MACHINE_NAME,
+            body = Self::push(Self::func(body)),
+            handler = Self::push(Self::func(handler)),
+            finally = Self::push(Self::func(finally))
+        )
+    }
+
+    fn yield_value(value: impl ToString) -> String {
+        format!("{}{}.yield_value()?;", value.to_string(), MACHINE_NAME)
+    }
+
+    fn await_value(value: impl ToString) -> String {
+        format!("{}{}.await_value()?;", value.to_string(), MACHINE_NAME)
+    }
+
     fn assign(pointer_value: impl ToString) -> String {
         format!(
             "{pointer_value}{MACHINE}.assign();",
@@ -98,8 +212,29 @@ impl Target for Rust {
         )
     }
 
-    fn dotname(head: Value, tail: Vec<Identifier>) -> String {
-        let mut result = Compile::<Self>::compile(head).unwrap();
+    fn slice(
+        value: impl ToString,
+        start: Option<String>,
+        end: Option<String>,
+        step: Option<String>,
+    ) -> String {
+        format!(
+            "{value}{bounds}{MACHINE}.slice({start}, {end}, {step})?;",
+            value = value.to_string(),
+            bounds = [&start, &end, &step]
+                .iter()
+                .filter_map(|bound| bound.as_ref())
+                .cloned()
+                .collect::<String>(),
+            start = start.is_some(),
+            end = end.is_some(),
+            step = step.is_some(),
+            MACHINE = MACHINE_NAME
+        )
+    }
+
+    fn dotname(head: impl ToString, tail: Vec<Identifier>) -> String {
+        let mut result = head.to_string();
         for ident in tail {
             let Identifier(name) = ident;
             result += &(Self::push(Self::string(Self::quote(name))) + &format!("{}.index();", MACHINE_NAME));
@@ -107,12 +242,13 @@ impl Target for Rust {
         result
     }
 
-    fn indexname(head: Value, tail: Vec<Value>) -> String {
-        let mut result = Compile::<Self>::compile(head).unwrap();
+    fn indexname(head: impl ToString, tail: Vec<String>) -> String {
+        let mut result = head.to_string();
         for value in tail {
-            result += &(Compile::<Self>::compile(value).unwrap() + &format!("{}.index();", MACHINE_NAME));
+            result += &(value + &format!("{}.index();", MACHINE_NAME));
         }
         result
     }
 
-}
\ No newline at end of file
+}
+
@@ -1,6 +1,6 @@
 
 
-use crate::{Identifier, Value};
+use crate::{Identifier, Type, Value};
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -12,6 +12,40 @@ pub const MACHINE_NAME: &str = "xasm";
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub enum Error {
     InvalidFunctionName,
+    ModuleNotFound(String),
+    ModuleSyntax(String, String),
+    CyclicImport(Vec<String>),
+    InvalidParameters,
+    InvalidArguments,
+    InvalidDestructure,
+    /// A variant pattern named by an index, such as `variants[0](x)`
+    InvalidPattern,
+    /// A `test` block that isn't at the top level of a program
+    NestedTest,
+    /// A `defer` that isn't directly in the body of a function
+    DeferOutsideFunction,
+    YieldOutsideFunction,
+    AwaitOutsideAsync,
+    AsyncGenerator,
+    NonlocalOutsideFunction,
+    /// A `global` declaration in the named module. The module's top level
+    /// names belong to its namespace rather than to the program's globals,
+    /// so they can't be declared global.
+    GlobalInModule(String),
+    UnboundNonlocal(String),
+    DuplicateKeyword(String),
+    UnknownKeyword(String, String),
+    /// A value of the second type bound to the named variable or parameter,
+    /// where the first type is expected. A mismatched result of a function
+    /// is named `return`.
+    TypeMismatch(String, Type, Type),
+}
+
+/// Code that compiles, but is likely a mistake
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Warning {
+    /// A `match` without a wildcard or binding arm, which does nothing if no arm matches
+    NonExhaustiveMatch(Value),
 }
 
 pub trait Compile<T: Target> {
@@ -23,22 +57,119 @@ pub trait Target {
     fn quote(name: impl ToString) -> String {
         format!("\"{}\"", name.to_string())
     }
+    /// A list of strings holding each of `names`, in the syntax of the target
+    fn names(names: Vec<String>) -> String;
     fn push(value: impl ToString) -> String;
     fn load(value: impl ToString) -> String;
+    /// Pushes the builtin function of the machine named `name`. Unlike
+    /// loading a variable, this finds the builtin even where the program
+    /// binds a variable of the same name, so that the code a lowering
+    /// generates calls the builtin it means to.
+    fn builtin(name: impl ToString) -> String;
     fn store(value: impl ToString) -> String;
     fn number(value: impl ToString) -> String;
     fn string(value: impl ToString) -> String;
     fn copy(value: impl ToString) -> String;
     fn func(value: impl ToString) -> String;
+    /// Like `func`, but calling the function returns a generator object
+    /// instead of running `value`. Each call to the generator's `next` method
+    /// runs the body, with the call's arguments still on its stack, until the
+    /// next `yield_value`, and returns the yielded value.
+    fn generator(value: impl ToString) -> String;
+    /// Like `func`, but calling the function starts running `value`
+    /// concurrently with the caller, and immediately returns a future
+    /// that resolves to the function's result. The Rust target wraps the
+    /// function in a future polled by the machine's executor, and the Go
+    /// target runs it in a goroutine that sends its result on a channel.
+    fn async_func(value: impl ToString) -> String;
+    /// Pops the function on top of the stack and pushes it back sharing each
+    /// of the named `variables` with the current scope by reference, so that
+    /// the function sees assignments made to them in the current scope, before
+    /// or after it was defined. A function only assigns to a captured variable
+    /// after declaring it `nonlocal`, since assigning to a name otherwise binds
+    /// a variable of the function's own, which isn't captured. Variables that
+    /// aren't bound in the current scope, such as builtins, are looked up when
+    /// the function runs.
+    fn capture(variables: Vec<String>) -> String;
+    /// Makes the named variables refer to the global variables of the same
+    /// names for the rest of the current function, so that storing to them
+    /// updates the globals.
+    fn global(names: Vec<String>) -> String;
+    /// Makes the named variables refer to the variables the current function
+    /// captured from the scope it was defined in for the rest of the function,
+    /// so that storing to them updates that scope.
+    fn nonlocal(names: Vec<String>) -> String;
     fn foreign_func(value: impl ToString) -> String;
     fn while_loop(condition: impl ToString, body: impl ToString) -> String;
     fn if_then_else(
         condition: impl ToString,
         then_fn: impl ToString,
         else_fn: impl ToString) -> String;
-    fn call(func: impl ToString) -> String;
-    fn method_call(method_name: impl ToString) -> String;
+    /// Calls a function with the argument count pushed by `argc` on top of
+    /// its arguments. The arguments are pushed in reverse, so that the first
+    /// argument sits directly beneath the count. `keywords` pairs the name of
+    /// each keyword argument with the code that pushes its value. The
+    /// function is called as it is: calls are compiled to check for a
+    /// `__call__` method before they get here.
+    fn call(func: impl ToString, argc: impl ToString, keywords: Vec<(String, String)>) -> String;
+    /// Like `call`, but the method is looked up on the object beneath the
+    /// argument count, which is passed as an extra leading argument.
+    fn method_call(
+        method_name: impl ToString,
+        argc: impl ToString,
+        keywords: Vec<(String, String)>) -> String;
+    /// Pops the argument count of a call, and leaves exactly one value on the
+    /// stack for each of the named `parameters`, binding keyword arguments by
+    /// name. Missing arguments are filled from `defaults`, which belong to the
+    /// last named parameters. If `variadic`, surplus positional arguments are
+    /// collected into a list beneath them.
+    fn arguments(parameters: Vec<String>, defaults: Vec<String>, variadic: bool) -> String;
+    /// Pushes an argument beneath the running argument count, incrementing it.
+    fn argument(value: impl ToString) -> String;
+    /// Pushes each element of a list beneath the running argument count,
+    /// incrementing it by the length of the list.
+    fn spread(list: impl ToString) -> String;
+    /// Pops the value pushed by `value` and raises it as an error, unwinding
+    /// to the nearest enclosing `try_catch`. The Rust target propagates errors
+    /// as the `Err` of the `Result` returned by every function, and the Go
+    /// target panics and recovers.
+    fn throw(value: impl ToString) -> String;
+    /// Runs `body`. If it raises an error, the error is pushed and `handler`
+    /// runs. `finally` runs last in either case, and an error raised by the
+    /// handler propagates once it has.
+    fn try_catch(body: impl ToString, handler: impl ToString, finally: impl ToString) -> String;
+    /// Pops the value pushed by `value`, and suspends the enclosing generator,
+    /// handing the value to the caller of `next`.
+    fn yield_value(value: impl ToString) -> String;
+    /// Pops the future pushed by `value`, suspends the enclosing function
+    /// until it resolves, and pushes its result. A value that isn't a future
+    /// is pushed back as it is.
+    fn await_value(value: impl ToString) -> String;
     fn assign(pointer_value: impl ToString) -> String;
-    fn dotname(head: Value, tail: Vec<Identifier>) -> String;
-    fn indexname(head: Value, tail: Vec<Value>) -> String;
+    /// Slices the value pushed by `value` with whichever bounds are present.
+    /// Negative bounds count back from the end of the value, and a negative
+    /// step walks it in reverse.
+    fn slice(
+        value: impl ToString,
+        start: Option<String>,
+        end: Option<String>,
+        step: Option<String>) -> String;
+    /// Looks up each of the members in `tail` in turn, starting from the
+    /// value pushed by `head`
+    fn dotname(head: impl ToString, tail: Vec<Identifier>) -> String;
+    /// Indexes by each of the keys pushed by the code in `tail` in turn,
+    /// starting from the value pushed by `head`. Reads are compiled to check
+    /// for an `__index__` method before they get here.
+    fn indexname(head: impl ToString, tail: Vec<String>) -> String;
+}
+
+
+/// The values of keyword arguments are pushed in reverse,
+/// so that the value of the first keyword ends up on top
+pub(crate) fn keyword_values(keywords: &[(String, String)]) -> String {
+    keywords.iter().rev().map(|(_, value)| value.clone()).collect()
+}
+
+pub(crate) fn keyword_names<T: Target>(keywords: &[(String, String)]) -> String {
+    T::names(keywords.iter().map(|(name, _)| name.clone()).collect())
 }
@@ -1,4 +1,7 @@
 use crate::target::*;
+use crate::module::{module_binding, module_global};
+use crate::capture::free_variables;
+use crate::visit::{self, Visit};
 
 use alloc::boxed::Box;
 use alloc::string::{String, ToString};
@@ -22,29 +25,144 @@ impl<T: Target> Compile<T> for Literal {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
-pub struct FnCall(pub Box<Value>, pub Vec<Value>);
+pub enum Argument {
+    Value(Value),
+    Spread(Value),
+    Keyword(Identifier, Value),
+}
+
+impl Argument {
+    pub fn value(&self) -> &Value {
+        match self {
+            Self::Value(value) | Self::Spread(value) | Self::Keyword(_, value) => value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct FnCall(pub Box<Value>, pub Vec<Argument>);
 
+/// Converting a value to a string with `str` calls the value's `__str__`
+/// method instead, if it defines one. The value is used more than once, so
+/// unless it is a name, it is evaluated beforehand into a temporary.
 impl<T: Target> Compile<T> for FnCall {
     fn compile(self) -> Result<String, Error> {
+        match self.converted() {
+            Some(Value::Literal(_)) | None => self.call::<T>(),
+            Some(value) if simple(value) => {
+                let value = value.clone();
+                Ok(T::if_then_else(
+                    Compile::<T>::compile(has(value.clone(), STR_METHOD))?,
+                    Compile::<T>::compile(Value::FnCall(FnCall(Box::new(member(value, STR_METHOD)), vec![])))?,
+                    self.call::<T>()?,
+                ))
+            }
+            Some(value) => Compile::<T>::compile(immediately(vec![
+                Expr::Assignment(Name::Name(Identifier(CONVERTED.to_string())), value.clone()),
+                Expr::Value(Value::FnCall(FnCall(self.0, vec![Argument::Value(variable(CONVERTED))]))),
+            ])),
+        }
+    }
+}
+
+impl FnCall {
+    /// The value this call converts to a string, if it calls `str` with a single argument
+    fn converted(&self) -> Option<&Value> {
+        match (&*self.0, &self.1[..]) {
+            (Value::Name(Name::Name(Identifier(name))), [Argument::Value(value)]) if name == "str" => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Compiles this call. A function that is an object defining a
+    /// `__call__` method is called through that method instead, with the
+    /// same arguments. Functions and builtins are called directly.
+    fn call<T: Target>(self) -> Result<String, Error> {
         let FnCall(function, arguments) = self;
-        let compiled_args = arguments
+        let direct = match &*function {
+            Value::Name(Name::Name(name)) => name.as_builtin().is_some(),
+            Value::Name(Name::DotName(_, _)) | Value::Function(_) | Value::Literal(_) => true,
+            _ => false,
+        };
+        // the function is used more than once, so it is evaluated beforehand into a temporary
+        if !direct && !simple(&function) {
+            return Compile::<T>::compile(immediately(vec![
+                Expr::Assignment(Name::Name(Identifier(CALLEE.to_string())), *function),
+                Expr::Value(Value::FnCall(FnCall(Box::new(variable(CALLEE)), arguments))),
+            ]));
+        }
+
+        // Keyword arguments are passed separately, and must come last
+        let positional = arguments
             .iter()
-            .rev()
-            .map(|arg| {
-                let value = arg.clone();
-                Compile::<T>::compile(value).unwrap()
-            })
-            .map(T::copy)
-            .collect::<String>();
+            .take_while(|arg| !matches!(arg, Argument::Keyword(_, _)))
+            .cloned()
+            .collect::<Vec<Argument>>();
+        let mut keywords: Vec<(String, String)> = vec![];
+        for arg in &arguments[positional.len()..] {
+            match arg.clone() {
+                Argument::Keyword(Identifier(name), value) => {
+                    if keywords.iter().any(|(other, _)| *other == name) {
+                        return Err(Error::DuplicateKeyword(name));
+                    }
+                    keywords.push((name, T::copy(Compile::<T>::compile(value)?)));
+                }
+                _ => return Err(Error::InvalidArguments),
+            }
+        }
+
+        // Without a spread, the number of arguments is known at compile time
+        // and is pushed after them. Otherwise, a running count is kept on top
+        // of the stack while the arguments are pushed beneath it.
+        let (compiled_args, argc) = if positional.iter().all(|arg| matches!(arg, Argument::Value(_))) {
+            (
+                positional
+                    .iter()
+                    .rev()
+                    .map(|arg| match arg.clone() {
+                        Argument::Value(value) => Compile::<T>::compile(value).map(T::copy),
+                        _ => unreachable!(),
+                    })
+                    .collect::<Result<String, Error>>()?,
+                T::push(T::number(positional.len())),
+            )
+        } else {
+            (
+                T::push(T::number(0))
+                    + &positional
+                        .iter()
+                        .rev()
+                        .map(|arg| match arg.clone() {
+                            Argument::Value(value) => {
+                                Compile::<T>::compile(value).map(|v| T::argument(T::copy(v)))
+                            }
+                            Argument::Spread(value) => {
+                                Compile::<T>::compile(value).map(|v| T::spread(T::copy(v)))
+                            }
+                            Argument::Keyword(_, _) => unreachable!(),
+                        })
+                        .collect::<Result<String, Error>>()?,
+                String::new(),
+            )
+        };
 
         if let Value::Name(Name::DotName(head, idents)) = (*function).clone() {
             let actual_idents = idents[..idents.len() - 1].to_vec();
             let Identifier(method_name) = &idents[idents.len() - 1];
             Ok(compiled_args
                 + &Compile::<T>::compile(Name::DotName(head, actual_idents))?
-                + &T::method_call(T::string(T::quote(method_name))))
+                + &T::method_call(T::string(T::quote(method_name)), argc, keywords))
+        } else if direct {
+            Ok(compiled_args + &T::call(Compile::<T>::compile(*function)?, argc, keywords))
         } else {
-            Ok(compiled_args + &T::call(Compile::<T>::compile((*function).clone())?))
+            // the branches run on the same stack, so both find the arguments pushed before them
+            Ok(compiled_args
+                + &T::if_then_else(
+                    Compile::<T>::compile(has((*function).clone(), CALL_METHOD))?,
+                    Compile::<T>::compile((*function).clone())?
+                        + &T::method_call(T::string(T::quote(CALL_METHOD)), argc.clone(), keywords.clone()),
+                    T::call(Compile::<T>::compile(*function)?, argc, keywords),
+                ))
         }
     }
 }
@@ -52,6 +170,23 @@ impl<T: Target> Compile<T> for FnCall {
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub struct Identifier(pub String);
 
+/// Lowerings call builtins by their names after this prefix. Identifiers in
+/// source can't contain it, so no variable a program binds can shadow them.
+const BUILTIN_PREFIX: &str = "builtin::";
+
+impl Identifier {
+    /// The name lowerings refer to the builtin `name` by, which is compiled
+    /// to the builtin itself rather than to a variable of the same name
+    pub fn builtin(name: &str) -> Self {
+        Identifier(format!("{}{}", BUILTIN_PREFIX, name))
+    }
+
+    /// The builtin this name refers to, if it is one
+    pub fn as_builtin(&self) -> Option<&str> {
+        self.0.strip_prefix(BUILTIN_PREFIX)
+    }
+}
+
 impl<T: Target> Compile<T> for Identifier {
     fn compile(self) -> Result<String, Error> {
         let Identifier(name) = self;
@@ -60,29 +195,188 @@ impl<T: Target> Compile<T> for Identifier {
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
-pub struct Function(pub Vec<Identifier>, pub Suite);
+pub enum Parameter {
+    Name(Identifier),
+    Default(Identifier, Value),
+    Rest(Identifier),
+    /// A list or dictionary pattern that the argument is destructured into
+    Pattern(Destructure),
+    /// A parameter with a type annotation, which is only used by the checker
+    Typed(Box<Parameter>, Type),
+}
+
+impl Parameter {
+    /// The name a keyword argument binds this parameter by, if any
+    pub fn name(&self) -> Option<&Identifier> {
+        match self {
+            Self::Name(name) | Self::Default(name, _) | Self::Rest(name) => Some(name),
+            Self::Pattern(_) => None,
+            Self::Typed(parameter, _) => parameter.name(),
+        }
+    }
+
+    /// This parameter without its type annotation
+    pub fn untyped(&self) -> &Parameter {
+        match self {
+            Self::Typed(parameter, _) => parameter.untyped(),
+            otherwise => otherwise,
+        }
+    }
+
+    /// The type this parameter is annotated with, if any
+    pub fn annotation(&self) -> Option<&Type> {
+        match self {
+            Self::Typed(_, annotation) => Some(annotation),
+            _ => None,
+        }
+    }
+}
+
+/// The name of a type, such as `num`, `str`, or a class. Values of type `any`
+/// are compatible with every other type.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Type(pub Identifier);
+
+impl Type {
+    pub fn new(name: &str) -> Self {
+        Self(Identifier(name.to_string()))
+    }
+
+    /// Whether a value of type `other` can be used where this type is expected
+    pub fn accepts(&self, other: &Type) -> bool {
+        let any = Type::new("any");
+        *self == any || *other == any || self == other
+    }
+}
+
+/// The target of a destructuring assignment
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Destructure {
+    Name(Name),
+    List(Vec<Destructure>),
+    Dict(Vec<Identifier>),
+}
+
+impl Destructure {
+    /// Every variable this target binds
+    pub fn names(&self) -> Vec<Identifier> {
+        match self {
+            Self::Name(Name::Name(name)) => vec![name.clone()],
+            Self::Name(_) => vec![],
+            Self::List(targets) => targets.iter().flat_map(Self::names).collect(),
+            Self::Dict(names) => names.clone(),
+        }
+    }
+
+    /// Pops the value on top of the stack and binds it to this target.
+    /// Lists and dictionaries are held in a temporary named after how
+    /// deeply they are nested while their elements are bound.
+    fn unpack<T: Target>(self, depth: usize) -> Result<String, Error> {
+        let temporary = Identifier(format!("__destructure{}", depth));
+        let element = |name: Name| Compile::<T>::compile(Value::Name(name)).map(T::copy);
+
+        match self {
+            Self::Name(Name::Name(name)) => Ok(T::store(Compile::<T>::compile(name)?)),
+            Self::Name(name) => Ok(T::assign(Compile::<T>::compile(name)?)),
+            Self::List(targets) => {
+                let mut result = T::store(Compile::<T>::compile(temporary.clone())?);
+                for (i, target) in targets.into_iter().enumerate() {
+                    result += &element(Name::IndexName(
+                        Box::new(Value::Name(Name::Name(temporary.clone()))),
+                        vec![number(i)],
+                    ))?;
+                    result += &target.unpack::<T>(depth + 1)?;
+                }
+                Ok(result)
+            }
+            Self::Dict(names) => {
+                let mut result = T::store(Compile::<T>::compile(temporary.clone())?);
+                for name in names {
+                    result += &element(Name::DotName(
+                        Box::new(Value::Name(Name::Name(temporary.clone()))),
+                        vec![name.clone()],
+                    ))?;
+                    result += &T::store(Compile::<T>::compile(name)?);
+                }
+                Ok(result)
+            }
+        }
+    }
+}
+
+/// A function's parameters, body, the type it is annotated to return, if
+/// any, and whether it is asynchronous
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Function(pub Vec<Parameter>, pub Suite, pub Option<Type>, pub bool);
 
 impl<T: Target> Compile<T> for Function {
     fn compile(self) -> Result<String, Error> {
-        let Function(parameters, body) = self;
+        let captures = free_variables(&self)
+            .into_iter()
+            .map(|Identifier(name)| name)
+            .collect::<Vec<String>>();
+        let Function(parameters, body, _, asynchronous) = self;
+        let parameters = parameters.iter().map(|p| p.untyped().clone()).collect::<Vec<Parameter>>();
+
+        // Parameters with defaults must follow the required parameters,
+        // and a rest parameter can only come last
+        let mut named = vec![];
+        let mut defaults = vec![];
+        let mut variadic = false;
+        for (i, parameter) in parameters.iter().enumerate() {
+            match parameter {
+                _ if variadic => return Err(Error::InvalidParameters),
+                Parameter::Name(_) | Parameter::Pattern(_) if !defaults.is_empty() => {
+                    return Err(Error::InvalidParameters)
+                }
+                Parameter::Name(Identifier(name)) => named.push(name.clone()),
+                // A pattern can't be bound by keyword, so it gets a name that can't be written
+                Parameter::Pattern(_) => named.push(format!("{}", i)),
+                Parameter::Default(Identifier(name), value) => {
+                    named.push(name.clone());
+                    defaults.push(Compile::<T>::compile(value.clone())?);
+                }
+                Parameter::Rest(_) => variadic = true,
+                Parameter::Typed(_, _) => unreachable!(),
+            }
+        }
 
         let stores = parameters
-            .iter()
-            .map(|s| T::store(Compile::<T>::compile((*s).clone()).unwrap()))
-            .collect::<String>();
+            .into_iter()
+            .map(|p| match p {
+                Parameter::Pattern(pattern) => pattern.unpack::<T>(0),
+                Parameter::Name(name) | Parameter::Default(name, _) | Parameter::Rest(name) => {
+                    Compile::<T>::compile(name).map(T::store)
+                }
+                Parameter::Typed(_, _) => unreachable!(),
+            })
+            .collect::<Result<String, Error>>()?;
 
-        Ok(T::push(T::func(stores + &Compile::<T>::compile(body)?)))
+        let generator = body.yields();
+        let body = T::arguments(named, defaults, variadic) + &stores + &Compile::<T>::compile(body)?;
+        let function = T::push(match (asynchronous, generator) {
+            (true, true) => return Err(Error::AsyncGenerator),
+            (true, false) => T::async_func(body),
+            (false, true) => T::generator(body),
+            (false, false) => T::func(body),
+        });
+        if captures.is_empty() {
+            Ok(function)
+        } else {
+            Ok(function + &T::capture(captures))
+        }
     }
 }
 
+/// A named function, and the `///` comments written directly before it, if any
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
-pub struct FunctionDef(pub Name, pub Function);
+pub struct FunctionDef(pub Name, pub Function, pub Option<String>);
 
 impl<T: Target> Compile<T> for FunctionDef {
     fn compile(self) -> Result<String, Error> {
-        let FunctionDef(name, function) = self;
+        let FunctionDef(name, function, _) = self;
 
-        Ok(Compile::<T>::compile(Expr::Assignment(name, Value::Function(function)))?)
+        Compile::<T>::compile(Expr::Assignment(name, Value::Function(function)))
     }
 }
 
@@ -92,22 +386,388 @@ pub enum Value {
     Literal(Literal),
     FnCall(FnCall),
     Function(Function),
+    /// A value chosen by a condition, where each branch leaves exactly one value on the stack
+    Conditional(Box<Value>, Box<Value>, Box<Value>),
+    Slice(Box<Value>, Slice),
+    List(Vec<Element>),
+    Dict(Vec<Entry>),
+    Comprehension(Box<Comprehension>),
+    Operation(Box<Value>, Operator, Box<Value>),
+    /// Waits for the future a value evaluates to, and evaluates to its result
+    Await(Box<Value>),
+}
+
+impl Value {
+    /// The value a list, dictionary, comprehension or operation is compiled
+    /// as, which is made of calls and loops. Any other value is returned as it is.
+    pub fn desugar(&self) -> Value {
+        match self {
+            Self::List(elements) => Value::FnCall(FnCall(
+                Box::new(Value::Name(Name::Name(Identifier::builtin("list")))),
+                elements
+                    .iter()
+                    .map(|element| match element.clone() {
+                        Element::Value(value) => Argument::Value(value),
+                        Element::Spread(value) => Argument::Spread(value),
+                    })
+                    .collect(),
+            )),
+            Self::Dict(entries) => {
+                let mut exprs = vec![Expr::Assignment(
+                    Name::Name(Identifier(COLLECTION.to_string())),
+                    builtin("dict", vec![]),
+                )];
+                for entry in entries.clone() {
+                    match entry {
+                        Entry::Pair(key, value) => exprs.push(Expr::Assignment(
+                            Name::IndexName(Box::new(variable(COLLECTION)), vec![key]),
+                            value,
+                        )),
+                        Entry::Spread(value) => {
+                            exprs.push(Expr::Assignment(Name::Name(Identifier(SPREAD.to_string())), value));
+                            exprs.extend(for_each(
+                                Destructure::Name(Name::Name(Identifier(SPREAD_KEY.to_string()))),
+                                Value::FnCall(FnCall(Box::new(member(variable(SPREAD), "keys")), vec![])),
+                                vec![Expr::Assignment(
+                                    Name::IndexName(Box::new(variable(COLLECTION)), vec![variable(SPREAD_KEY)]),
+                                    index(variable(SPREAD), variable(SPREAD_KEY)),
+                                )],
+                                0,
+                            ));
+                        }
+                    }
+                }
+                exprs.push(Expr::Value(variable(COLLECTION)));
+                immediately(exprs)
+            }
+            Self::Comprehension(comprehension) => comprehension.lower(),
+            Self::Operation(left, operator, right) => operator.apply((**left).clone(), (**right).clone()),
+            otherwise => otherwise.clone(),
+        }
+    }
+}
+
+/// An infix operator. An operator calls a method of its left operand with its
+/// right operand if the left operand defines it, so that a class can overload
+/// an operator by defining the method, and calls a builtin function otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl Operator {
+    /// The name of the method this operator calls
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::Equal => "__eq__",
+            Self::NotEqual => "__ne__",
+            Self::Less => "__lt__",
+            Self::LessEqual => "__le__",
+            Self::Greater => "__gt__",
+            Self::GreaterEqual => "__ge__",
+            Self::Add => "__add__",
+            Self::Subtract => "__sub__",
+            Self::Multiply => "__mul__",
+            Self::Divide => "__div__",
+            Self::Remainder => "__mod__",
+        }
+    }
+
+    /// The name of the builtin function this operator calls on values that don't define its method
+    pub fn builtin(&self) -> &'static str {
+        match self {
+            Self::Equal => "eq",
+            Self::NotEqual => "ne",
+            Self::Less => "less",
+            Self::LessEqual => "less_eq",
+            Self::Greater => "greater",
+            Self::GreaterEqual => "greater_eq",
+            Self::Add => "add",
+            Self::Subtract => "sub",
+            Self::Multiply => "mul",
+            Self::Divide => "div",
+            Self::Remainder => "rem",
+        }
+    }
+
+    /// Calls this operator's method on `left` with `right`
+    pub fn overload(&self, left: Value, right: Value) -> Value {
+        Value::FnCall(FnCall(Box::new(member(left, self.method())), vec![Argument::Value(right)]))
+    }
+
+    /// Applies this operator to its operands. A literal can't define the
+    /// method, so the builtin is called on it directly. Otherwise, the `has`
+    /// builtin decides whether the left operand defines the method, and each
+    /// operand that isn't a name or a literal is evaluated once beforehand.
+    pub fn apply(&self, left: Value, right: Value) -> Value {
+        let dispatch = |left: Value, right: Value| {
+            Value::Conditional(
+                Box::new(has(left.clone(), self.method())),
+                Box::new(self.overload(left.clone(), right.clone())),
+                Box::new(builtin(self.builtin(), vec![left, right])),
+            )
+        };
+        match (&left, &right) {
+            (Value::Literal(_), _) => builtin(self.builtin(), vec![left, right]),
+            (left, right) if simple(left) && simple(right) => dispatch(left.clone(), right.clone()),
+            _ => immediately(vec![
+                Expr::Assignment(Name::Name(Identifier(LEFT_OPERAND.to_string())), left),
+                Expr::Assignment(Name::Name(Identifier(RIGHT_OPERAND.to_string())), right),
+                Expr::Value(dispatch(variable(LEFT_OPERAND), variable(RIGHT_OPERAND))),
+            ]),
+        }
+    }
+
+    /// How this operator is written in source
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::Remainder => "%",
+        }
+    }
+
+    /// How tightly this operator binds its operands
+    pub fn precedence(&self) -> usize {
+        match self {
+            Self::Equal | Self::NotEqual | Self::Less | Self::LessEqual | Self::Greater | Self::GreaterEqual => 0,
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide | Self::Remainder => 2,
+        }
+    }
+}
+
+/// An element of a list literal
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Element {
+    Value(Value),
+    /// The elements of another list
+    Spread(Value),
+}
+
+/// An entry of a dictionary literal
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Entry {
+    Pair(Value, Value),
+    /// The entries of another dictionary
+    Spread(Value),
+}
+
+/// A clause of a comprehension, which either binds each element of a list
+/// in turn, or skips the elements for which a condition doesn't hold
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Clause {
+    For(Destructure, Value),
+    If(Value),
+}
+
+/// A list or dictionary built from each combination of elements its clauses
+/// bind, where later clauses are nested inside earlier ones
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Comprehension {
+    List(Value, Vec<Clause>),
+    Dict(Value, Value, Vec<Clause>),
+}
+
+impl Comprehension {
+    /// Lowers this comprehension to a function that builds it with loops,
+    /// called immediately so that the names it binds don't escape it
+    pub fn lower(&self) -> Value {
+        let (create, add, clauses) = match self.clone() {
+            Self::List(element, clauses) => (
+                builtin("list", vec![]),
+                Expr::Value(Value::FnCall(FnCall(
+                    Box::new(member(variable(COLLECTION), "push")),
+                    vec![Argument::Value(element)],
+                ))),
+                clauses,
+            ),
+            Self::Dict(key, value, clauses) => (
+                builtin("dict", vec![]),
+                Expr::Assignment(Name::IndexName(Box::new(variable(COLLECTION)), vec![key]), value),
+                clauses,
+            ),
+        };
+
+        let body = clauses
+            .into_iter()
+            .enumerate()
+            .rev()
+            .fold(vec![add], |body, (depth, clause)| match clause {
+                Clause::For(target, items) => for_each(target, items, body, depth),
+                Clause::If(condition) => vec![Expr::IfThenElse(condition, Suite(body), Suite(vec![]))],
+            });
+
+        let mut exprs = vec![Expr::Assignment(Name::Name(Identifier(COLLECTION.to_string())), create)];
+        exprs.extend(body);
+        exprs.push(Expr::Value(variable(COLLECTION)));
+        immediately(exprs)
+    }
+}
+
+/// Whether a value can be used more than once without evaluating it again
+fn simple(value: &Value) -> bool {
+    matches!(value, Value::Name(Name::Name(_)) | Value::Literal(_))
+}
+
+/// Whether `value` defines the method named `method`
+fn has(value: Value, method: &str) -> Value {
+    builtin("has", vec![value, Value::Literal(Literal::String(method.to_string()))])
+}
+
+const INDEX_METHOD: &str = "__index__";
+const CALL_METHOD: &str = "__call__";
+const STR_METHOD: &str = "__str__";
+const INDEXED: &str = "__indexed";
+const INDEX_KEY: &str = "__index_key";
+const CALLEE: &str = "__callee";
+const CONVERTED: &str = "__converted";
+const LEFT_OPERAND: &str = "__left";
+const RIGHT_OPERAND: &str = "__right";
+const COLLECTION: &str = "__collection";
+const SPREAD: &str = "__spread";
+const SPREAD_KEY: &str = "__key";
+
+/// Runs `body` with `target` bound to each element of `items` in turn. The list
+/// and the position in it are kept in temporaries named after `depth`, so that
+/// loops can be nested.
+fn for_each(target: Destructure, items: Value, body: Vec<Expr>, depth: usize) -> Vec<Expr> {
+    let list = Identifier(format!("__items{}", depth));
+    let position = Identifier(format!("__index{}", depth));
+    let load = |name: &Identifier| Value::Name(Name::Name(name.clone()));
+
+    let mut exprs = vec![Expr::Destructure(vec![target], vec![index(load(&list), load(&position))])];
+    exprs.extend(body);
+    exprs.push(Expr::Assignment(
+        Name::Name(position.clone()),
+        builtin("add", vec![load(&position), number(1)]),
+    ));
+
+    vec![
+        Expr::Assignment(Name::Name(list.clone()), items),
+        Expr::Assignment(Name::Name(position.clone()), number(0)),
+        Expr::WhileLoop(
+            builtin("less", vec![load(&position), builtin("len", vec![load(&list)])]),
+            Suite(exprs),
+        ),
+    ]
+}
+
+/// Calls a function with the given body as soon as it is defined. If the
+/// body awaits, the function is asynchronous, and its result is awaited in
+/// turn, so that the body can await whatever the enclosing function can.
+fn immediately(body: Vec<Expr>) -> Value {
+    let mut awaits = Awaits(false);
+    body.iter().for_each(|expr| awaits.visit_expr(expr));
+    let Awaits(asynchronous) = awaits;
+
+    let call = Value::FnCall(FnCall(
+        Box::new(Value::Function(Function(vec![], Suite(body), None, asynchronous))),
+        vec![],
+    ));
+    if asynchronous {
+        Value::Await(Box::new(call))
+    } else {
+        call
+    }
+}
+
+/// Finds whether code awaits, not counting the functions it defines
+struct Awaits(bool);
+
+impl Visit for Awaits {
+    fn visit_value(&mut self, value: &Value) {
+        if let Value::Await(_) = value {
+            self.0 = true;
+        }
+        visit::visit_value(self, value)
+    }
+
+    fn visit_function(&mut self, _function: &Function) {}
 }
 
 impl<T: Target> Compile<T> for Value {
     fn compile(self) -> Result<String, Error> {
         match self {
             Self::Name(name) => match name {
-                Name::Name(n) => Compile::<T>::compile(n).and_then(|n| Ok(T::load(n))),
+                Name::Name(n) => match n.as_builtin() {
+                    Some(builtin) => Ok(T::builtin(builtin)),
+                    None => Compile::<T>::compile(n).map(|n| T::load(n)),
+                },
+                Name::IndexName(head, keys) => compile_index::<T>(*head, keys),
                 otherwise => Compile::<T>::compile(otherwise),
             },
             Self::Literal(l) => Compile::<T>::compile(l),
             Self::FnCall(f) => Compile::<T>::compile(f),
             Self::Function(f) => Compile::<T>::compile(f),
+            Self::Conditional(condition, then_value, else_value) => Ok(T::if_then_else(
+                Compile::<T>::compile(*condition)?,
+                Compile::<T>::compile(*then_value)?,
+                Compile::<T>::compile(*else_value)?,
+            )),
+            Self::Slice(value, Slice(start, end, step)) => {
+                let bound = |bound: Option<Box<Value>>| bound.map(|b| Compile::<T>::compile(*b)).transpose();
+                Ok(T::slice(Compile::<T>::compile(*value)?, bound(start)?, bound(end)?, bound(step)?))
+            }
+            Self::List(_) | Self::Dict(_) | Self::Comprehension(_) | Self::Operation(_, _, _) => {
+                Compile::<T>::compile(self.desugar())
+            }
+            Self::Await(value) => Ok(T::await_value(Compile::<T>::compile(*value)?)),
         }
     }
 }
 
+/// Reads a value indexed by each of `keys` in turn. A value that defines an
+/// `__index__` method is indexed by calling it with the key instead. The
+/// value and the key are used more than once, so unless they are names or
+/// literals, they are evaluated beforehand into temporaries. Assigning to
+/// an index always sets the item itself.
+fn compile_index<T: Target>(head: Value, mut keys: Vec<Value>) -> Result<String, Error> {
+    let key = match keys.pop() {
+        Some(key) => key,
+        None => return Compile::<T>::compile(head),
+    };
+    let value = if keys.is_empty() { head } else { Value::Name(Name::IndexName(Box::new(head), keys)) };
+    match (&value, &key) {
+        (Value::Literal(_), _) => Ok(T::indexname(Compile::<T>::compile(value)?, vec![Compile::<T>::compile(key)?])),
+        (value, key) if simple(value) && simple(key) => Ok(T::if_then_else(
+            Compile::<T>::compile(has(value.clone(), INDEX_METHOD))?,
+            Compile::<T>::compile(Value::FnCall(FnCall(
+                Box::new(member(value.clone(), INDEX_METHOD)),
+                vec![Argument::Value(key.clone())],
+            )))?,
+            T::indexname(Compile::<T>::compile(value.clone())?, vec![Compile::<T>::compile(key.clone())?]),
+        )),
+        _ => Compile::<T>::compile(immediately(vec![
+            Expr::Assignment(Name::Name(Identifier(INDEXED.to_string())), value),
+            Expr::Assignment(Name::Name(Identifier(INDEX_KEY.to_string())), key),
+            Expr::Value(Value::Name(Name::IndexName(Box::new(variable(INDEXED)), vec![variable(INDEX_KEY)]))),
+        ])),
+    }
+}
+
+/// The start, end and step of a slice, each of which may be omitted
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Slice(pub Option<Box<Value>>, pub Option<Box<Value>>, pub Option<Box<Value>>);
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub enum Name {
     Name(Identifier),
@@ -119,8 +779,11 @@ impl<T: Target> Compile<T> for Name {
     fn compile(self) -> Result<String, Error> {
         match self {
             Self::Name(n) => Compile::<T>::compile(n),
-            Self::DotName(head, tail) => Ok(T::dotname((*head).clone(), tail)),
-            Self::IndexName(head, tail) => Ok(T::indexname((*head).clone(), tail)),
+            Self::DotName(head, tail) => Ok(T::dotname(Compile::<T>::compile(*head)?, tail)),
+            Self::IndexName(head, tail) => Ok(T::indexname(
+                Compile::<T>::compile(*head)?,
+                tail.into_iter().map(Compile::<T>::compile).collect::<Result<Vec<String>, Error>>()?,
+            )),
         }
     }
 }
@@ -128,10 +791,39 @@ impl<T: Target> Compile<T> for Name {
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub enum Expr {
     Assignment(Name, Value),
+    /// A `let` binding, which may annotate the type of the variable
+    Declaration(Identifier, Option<Type>, Value),
     WhileLoop(Value, Suite),
     IfThenElse(Value, Suite, Suite),
     FunctionDef(FunctionDef),
     StructDef(StructDef),
+    EnumDef(EnumDef),
+    Import(String),
+    FromImport(String, Vec<Identifier>),
+    Throw(Value),
+    /// Throws the message, if any, when the condition doesn't hold
+    Assert(Value, Option<Value>),
+    /// A named block of code that is only compiled by `compile_tests`
+    Test(String, Suite),
+    Yield(Value),
+    TryCatch(Suite, Option<(Identifier, Suite)>, Suite),
+    /// Makes assignments to the names in the rest of the function update the global variables
+    Global(Vec<Identifier>),
+    /// Makes assignments to the names in the rest of the function update
+    /// the variables of the enclosing function that it captured
+    Nonlocal(Vec<Identifier>),
+    /// Evaluates the value once the rest of the enclosing function has run,
+    /// whether it finishes normally or raises an error. Deferred values
+    /// are evaluated in the reverse of the order they were deferred in.
+    /// A `defer` can only be written directly in the body of a function.
+    Defer(Value),
+    /// Binds the value to the name while the block runs, and calls its
+    /// `close` method afterwards, even if the block raises an error
+    With(Value, Identifier, Suite),
+    Match(Value, Vec<Arm>),
+    /// Binds each target to the value in the same position, evaluating all of
+    /// the values first. A single value is unpacked across several targets.
+    Destructure(Vec<Destructure>, Vec<Value>),
     Value(Value),
 }
 
@@ -142,6 +834,7 @@ impl<T: Target> Compile<T> for Expr {
                 Name::Name(n) => Ok(T::store(T::copy(Compile::<T>::compile(value)?) + &Compile::<T>::compile(n)?)),
                 otherwise => Ok(T::assign(T::copy(Compile::<T>::compile(value)?) + &Compile::<T>::compile(otherwise)?)),
             },
+            Self::Declaration(name, _, value) => Compile::<T>::compile(Expr::Assignment(Name::Name(name), value)),
             Self::WhileLoop(condition, body) => {
                 Ok(T::while_loop(Compile::<T>::compile(condition)?, Compile::<T>::compile(body)?))
             }
@@ -152,54 +845,433 @@ impl<T: Target> Compile<T> for Expr {
             )),
             Self::FunctionDef(function_def) => Ok(Compile::<T>::compile(function_def)?),
             Self::StructDef(struct_def) => Ok(Compile::<T>::compile(struct_def)?),
+            Self::EnumDef(enum_def) => Ok(Compile::<T>::compile(enum_def)?),
+            Self::Import(path) => Compile::<T>::compile(Expr::Assignment(
+                Name::Name(module_binding(&path)),
+                Value::Name(Name::Name(module_global(&path))),
+            )),
+            Self::FromImport(path, names) => Ok(names
+                .into_iter()
+                .map(|name| {
+                    Compile::<T>::compile(Expr::Assignment(
+                        Name::Name(name.clone()),
+                        Value::Name(Name::DotName(
+                            Box::new(Value::Name(Name::Name(module_global(&path)))),
+                            vec![name],
+                        )),
+                    ))
+                })
+                .collect::<Result<String, Error>>()?),
+            Self::Throw(value) => Ok(T::throw(Compile::<T>::compile(value)?)),
+            Self::Assert(condition, message) => Compile::<T>::compile(Expr::IfThenElse(
+                condition,
+                Suite(vec![]),
+                Suite(vec![Expr::Throw(
+                    message.unwrap_or_else(|| Value::Literal(Literal::String(ASSERTION_FAILED.to_string()))),
+                )]),
+            )),
+            Self::Test(_, _) => Ok(String::new()),
+            Self::Yield(value) => Ok(T::yield_value(Compile::<T>::compile(value)?)),
+            Self::TryCatch(body, handler, finally) => Ok(T::try_catch(
+                Compile::<T>::compile(body)?,
+                match handler {
+                    Some((name, handler)) => {
+                        T::store(Compile::<T>::compile(name)?) + &Compile::<T>::compile(handler)?
+                    }
+                    // Without a handler, the error is raised again once `finally` has run
+                    None => T::throw(String::new()),
+                },
+                Compile::<T>::compile(finally)?,
+            )),
+            Self::Destructure(mut targets, values) => {
+                if values.len() == 1 && targets.len() > 1 {
+                    targets = vec![Destructure::List(targets)];
+                }
+                if targets.len() != values.len() {
+                    return Err(Error::InvalidDestructure);
+                }
+
+                let mut result = values
+                    .into_iter()
+                    .rev()
+                    .map(|value| Compile::<T>::compile(value).map(T::copy))
+                    .collect::<Result<String, Error>>()?;
+                for target in targets {
+                    result += &target.unpack::<T>(0)?;
+                }
+                Ok(result)
+            }
+            Self::Global(names) => Ok(T::global(names.into_iter().map(|Identifier(name)| name).collect())),
+            Self::Nonlocal(names) => Ok(T::nonlocal(names.into_iter().map(|Identifier(name)| name).collect())),
+            Self::Defer(_) => Compile::<T>::compile(Suite(vec![self])),
+            Self::With(value, name, Suite(body)) => {
+                let close = Value::FnCall(FnCall(
+                    Box::new(member(Value::Name(Name::Name(name.clone())), "close")),
+                    vec![],
+                ));
+                let mut exprs = vec![Expr::Assignment(Name::Name(name), value), Expr::Defer(close)];
+                exprs.extend(body);
+                Compile::<T>::compile(Suite(exprs))
+            }
+            Self::Match(subject, arms) => {
+                if !arms.iter().all(|Arm(patterns, _)| patterns.iter().all(Pattern::is_valid)) {
+                    return Err(Error::InvalidPattern);
+                }
+                Compile::<T>::compile(Suite(lower_match(subject, arms)))
+            }
             Self::Value(value) => Ok(Compile::<T>::compile(value)?),
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Pattern {
+    Wildcard,
+    Binding(Identifier),
+    Literal(Literal),
+    /// A name whose value is compared against, such as `Color.Red`
+    Value(Name),
+    List(Vec<Pattern>),
+    /// An enum variant whose fields are matched in order, such as `Color.Rgb(r, g, _)`
+    Variant(Name, Vec<Pattern>),
+}
+
+impl Pattern {
+    /// Whether this pattern matches any value
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Self::Wildcard | Self::Binding(_))
+    }
+
+    /// Whether this pattern can be matched against. A variant must be named
+    /// by its enum and a member, or by a bare name, and not by an index.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Variant(Name::IndexName(_, _), _) => false,
+            Self::List(patterns) | Self::Variant(_, patterns) => patterns.iter().all(Self::is_valid),
+            _ => true,
+        }
+    }
+}
+
+/// An arm of a `match`, which runs its body if any of its patterns match
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Arm(pub Vec<Pattern>, pub Suite);
+
+const ASSERTION_FAILED: &str = "assertion failed";
+const TEST_ERROR: &str = "__error";
+const TEST_FAILURES: &str = "__failures";
+const TESTS_FAILED: &str = "tests failed";
+
+/// Replaces the `test` blocks at the top level of a program with code that
+/// runs each of them after the rest of the program, in its own scope, and
+/// prints whether it passed or failed. Once every test has run, a summary is
+/// printed, and an error is thrown if any of them failed.
+pub fn test_harness(program: Suite) -> Suite {
+    let Suite(exprs) = program;
+    let (tests, mut exprs): (Vec<Expr>, Vec<Expr>) =
+        exprs.into_iter().partition(|expr| matches!(expr, Expr::Test(_, _)));
+    let failures = || Name::Name(Identifier(TEST_FAILURES.to_string()));
+    let text = |text: &str| Value::Literal(Literal::String(text.to_string()));
+    exprs.push(Expr::Assignment(failures(), number(0)));
+    let count = tests.len();
+
+    let report = |name: &str, outcome: &str, error: Option<Value>| {
+        let mut arguments = vec![Value::Literal(Literal::String(format!("test {} ... {}", name, outcome)))];
+        arguments.extend(error);
+        Expr::Value(builtin("println", arguments))
+    };
+    for test in tests {
+        if let Expr::Test(name, body) = test {
+            exprs.push(Expr::TryCatch(
+                Suite(vec![
+                    Expr::Value(Value::FnCall(FnCall(Box::new(Value::Function(Function(vec![], body, None, false))), vec![]))),
+                    report(&name, "ok", None),
+                ]),
+                Some((
+                    Identifier(TEST_ERROR.to_string()),
+                    Suite(vec![
+                        report(&name, "FAILED", Some(variable(TEST_ERROR))),
+                        Expr::Assignment(failures(), builtin("add", vec![variable(TEST_FAILURES), number(1)])),
+                    ]),
+                )),
+                Suite(vec![]),
+            ));
+        }
+    }
+
+    exprs.push(Expr::Value(builtin(
+        "println",
+        vec![
+            text("test result:"),
+            builtin("sub", vec![number(count), variable(TEST_FAILURES)]),
+            text("passed;"),
+            variable(TEST_FAILURES),
+            text("failed"),
+        ],
+    )));
+    exprs.push(Expr::IfThenElse(
+        builtin("eq", vec![variable(TEST_FAILURES), number(0)]),
+        Suite(vec![]),
+        Suite(vec![Expr::Throw(text(TESTS_FAILED))]),
+    ));
+    Suite(exprs)
+}
+
+const MATCH_SUBJECT: &str = "__match";
+const MATCH_ARM: &str = "__arm";
+
+fn variable(name: &str) -> Value {
+    Value::Name(Name::Name(Identifier(name.to_string())))
+}
+
+/// Calls the builtin `name`, whatever the program binds to that name
+pub(crate) fn builtin(name: &str, arguments: Vec<Value>) -> Value {
+    Value::FnCall(FnCall(
+        Box::new(Value::Name(Name::Name(Identifier::builtin(name)))),
+        arguments.into_iter().map(Argument::Value).collect(),
+    ))
+}
+
+fn number(n: usize) -> Value {
+    Value::Literal(Literal::Number(n.to_string()))
+}
+
+/// Lowers a `match` to comparisons. The subject is evaluated once, then the
+/// patterns are tried in order until one matches, binding its names and
+/// recording its arm. Finally, the body of the recorded arm runs. The subject
+/// and the arm are kept in temporaries named after how deeply other matches
+/// are nested in the arms, so that a `match` inside an arm has its own.
+pub fn lower_match(subject: Value, arms: Vec<Arm>) -> Vec<Expr> {
+    let depth = arms.iter().map(|Arm(_, body)| nested_matches(body)).max().unwrap_or(0);
+    let subject_name = Identifier(format!("{}{}", MATCH_SUBJECT, depth));
+    let arm_name = Identifier(format!("{}{}", MATCH_ARM, depth));
+    let load = |name: &Identifier| Value::Name(Name::Name(name.clone()));
+
+    let mut exprs = vec![
+        Expr::Assignment(Name::Name(subject_name.clone()), subject),
+        Expr::Assignment(Name::Name(arm_name.clone()), number(0)),
+    ];
+
+    for (i, Arm(patterns, _)) in arms.iter().enumerate() {
+        for pattern in patterns {
+            let chosen = vec![Expr::Assignment(Name::Name(arm_name.clone()), number(i + 1))];
+            exprs.push(Expr::IfThenElse(
+                builtin("eq", vec![load(&arm_name), number(0)]),
+                Suite(test_pattern(pattern, load(&subject_name), chosen)),
+                Suite(vec![]),
+            ));
+        }
+    }
+
+    let bodies = arms
+        .into_iter()
+        .enumerate()
+        .rev()
+        .fold(Suite(vec![]), |otherwise, (i, Arm(_, body))| {
+            Suite(vec![Expr::IfThenElse(
+                builtin("eq", vec![load(&arm_name), number(i + 1)]),
+                body,
+                otherwise,
+            )])
+        });
+
+    exprs.extend(bodies.0);
+    exprs
+}
+
+/// How deeply matches are nested in a block, leaving out the functions it defines
+fn nested_matches(suite: &Suite) -> usize {
+    let Suite(exprs) = suite;
+    exprs
+        .iter()
+        .map(|expr| match expr {
+            Expr::Match(_, arms) => 1 + arms.iter().map(|Arm(_, body)| nested_matches(body)).max().unwrap_or(0),
+            Expr::WhileLoop(_, body) | Expr::With(_, _, body) => nested_matches(body),
+            Expr::IfThenElse(_, then_body, else_body) => nested_matches(then_body).max(nested_matches(else_body)),
+            Expr::TryCatch(body, handler, finally) => nested_matches(body)
+                .max(handler.as_ref().map_or(0, |(_, handler)| nested_matches(handler)))
+                .max(nested_matches(finally)),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Runs `matched` if `pattern` matches `subject`. The names in the pattern
+/// are only bound once all of it has matched.
+fn test_pattern(pattern: &Pattern, subject: Value, matched: Vec<Expr>) -> Vec<Expr> {
+    let mut exprs = pattern_bindings(pattern, &subject);
+    exprs.extend(matched);
+    pattern_conditions(pattern, subject, exprs)
+}
+
+/// Binds each name in a pattern to the part of `subject` it matches
+fn pattern_bindings(pattern: &Pattern, subject: &Value) -> Vec<Expr> {
+    match pattern {
+        Pattern::Binding(name) => vec![Expr::Assignment(Name::Name(name.clone()), subject.clone())],
+        Pattern::List(patterns) | Pattern::Variant(_, patterns) => patterns
+            .iter()
+            .enumerate()
+            .flat_map(|(i, pattern)| pattern_bindings(pattern, &index(subject.clone(), number(i))))
+            .collect(),
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::Value(_) => vec![],
+    }
+}
+
+/// Runs `matched` if `pattern` matches `subject`, without binding any names.
+/// The type of the subject is tested before any of its elements are.
+fn pattern_conditions(pattern: &Pattern, subject: Value, matched: Vec<Expr>) -> Vec<Expr> {
+    let when = |condition: Value, matched: Vec<Expr>| vec![Expr::IfThenElse(condition, Suite(matched), Suite(vec![]))];
+    let is = |kind: &str| {
+        builtin("eq", vec![builtin("type", vec![subject.clone()]), Value::Literal(Literal::String(kind.to_string()))])
+    };
+    let elements = |patterns: &[Pattern], matched| {
+        patterns
+            .iter()
+            .enumerate()
+            .rev()
+            .fold(matched, |matched, (i, pattern)| {
+                pattern_conditions(pattern, index(subject.clone(), number(i)), matched)
+            })
+    };
+
+    match pattern {
+        Pattern::Wildcard | Pattern::Binding(_) => matched,
+        Pattern::Literal(literal) => when(builtin("eq", vec![subject.clone(), Value::Literal(literal.clone())]), matched),
+        Pattern::Value(name) => when(builtin("eq", vec![subject.clone(), Value::Name(name.clone())]), matched),
+        Pattern::List(patterns) => when(
+            is("list"),
+            when(
+                builtin("eq", vec![builtin("len", vec![subject.clone()]), number(patterns.len())]),
+                elements(patterns, matched),
+            ),
+        ),
+        // the enum a variant belongs to is compared too, unless the pattern names the variant alone
+        Pattern::Variant(name, patterns) => {
+            let (enumeration, Identifier(tag)) = match name {
+                Name::Name(variant) => (None, variant.clone()),
+                Name::DotName(head, members) => {
+                    let (variant, path) = members.split_last().unwrap();
+                    let enumeration = match path {
+                        [] => (**head).clone(),
+                        path => Value::Name(Name::DotName(head.clone(), path.to_vec())),
+                    };
+                    (Some(enumeration), variant.clone())
+                }
+                Name::IndexName(_, _) => unreachable!("variant patterns can't be named by an index"),
+            };
+            let mut fields = elements(patterns, matched);
+            if let Some(enumeration) = enumeration {
+                fields = when(
+                    builtin("eq", vec![member(subject.clone(), ENUM_NAME), member(enumeration, ENUM_NAME)]),
+                    fields,
+                );
+            }
+            when(
+                is("dict"),
+                when(
+                    builtin("eq", vec![member(subject.clone(), ENUM_TAG), Value::Literal(Literal::String(tag))]),
+                    fields,
+                ),
+            )
+        }
+    }
+}
+
+/// Accesses a member of a value, extending the chain of members if it is already a member access
+fn member(value: Value, name: &str) -> Value {
+    let name = Identifier(name.to_string());
+    match value {
+        Value::Name(Name::DotName(head, mut members)) => {
+            members.push(name);
+            Value::Name(Name::DotName(head, members))
+        }
+        otherwise => Value::Name(Name::DotName(Box::new(otherwise), vec![name])),
+    }
+}
+
+/// Indexes a value, extending the chain of indices if it is already an index
+fn index(value: Value, key: Value) -> Value {
+    match value {
+        Value::Name(Name::IndexName(head, mut keys)) => {
+            keys.push(key);
+            Value::Name(Name::IndexName(head, keys))
+        }
+        otherwise => Value::Name(Name::IndexName(Box::new(otherwise), vec![key])),
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub struct Suite(pub Vec<Expr>);
 
+impl Suite {
+    /// Whether this suite yields, making the function it belongs to a generator.
+    /// Yields inside nested functions belong to those functions instead.
+    pub fn yields(&self) -> bool {
+        let Suite(exprs) = self;
+        exprs.iter().any(|expr| match expr {
+            Expr::Yield(_) => true,
+            Expr::WhileLoop(_, body) => body.yields(),
+            Expr::IfThenElse(_, then_body, else_body) => then_body.yields() || else_body.yields(),
+            Expr::TryCatch(body, handler, finally) => {
+                body.yields() || handler.iter().any(|(_, handler)| handler.yields()) || finally.yields()
+            }
+            Expr::Match(_, arms) => arms.iter().any(|Arm(_, body)| body.yields()),
+            Expr::With(_, _, body) => body.yields(),
+            _ => false,
+        })
+    }
+}
+
+const DEFERRED: &str = "__deferred";
+
 impl<T: Target> Compile<T> for Suite {
     fn compile(self) -> Result<String, Error> {
-        let Suite(exprs) = self;
-        Ok(exprs
-            .iter()
-            .map(|c| Compile::<T>::compile(c.clone()).unwrap())
-            .collect::<String>())
+        let Suite(mut exprs) = self;
+
+        // Everything after a `defer` runs in a `try` whose `finally` evaluates
+        // the deferred value, which is stored so that it doesn't stay on the stack
+        if let Some(position) = exprs.iter().position(|expr| matches!(expr, Expr::Defer(_))) {
+            let rest = exprs.split_off(position + 1);
+            if let Some(Expr::Defer(value)) = exprs.pop() {
+                exprs.push(Expr::TryCatch(
+                    Suite(rest),
+                    None,
+                    Suite(vec![Expr::Assignment(Name::Name(Identifier(DEFERRED.to_string())), value)]),
+                ));
+            }
+        }
+
+        exprs
+            .into_iter()
+            .map(Compile::<T>::compile)
+            .collect::<Result<String, Error>>()
     }
 }
 
+/// A class, its methods, and the `///` comments written directly before it, if any
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
-pub struct StructDef(pub Name, pub Vec<FunctionDef>);
+pub struct StructDef(pub Name, pub Vec<FunctionDef>, pub Option<String>);
 
-impl<T: Target> Compile<T> for StructDef {
-    fn compile(self) -> Result<String, Error> {
-        let StructDef(name, function_defs) = self;
-        let mut exprs = vec![Expr::Assignment(
-            Name::Name(Identifier("self".to_string())),
-            Value::FnCall(FnCall(
-                Box::new(Value::Name(Name::Name(Identifier("dict".to_string())))),
-                vec![],
-            )),
-        )];
+impl StructDef {
+    /// The function that creates an instance of this class, by defining
+    /// each method on a new dictionary
+    pub fn constructor(self) -> Function {
+        let StructDef(_, function_defs, _) = self;
+        let mut exprs = vec![Expr::Assignment(Name::Name(Identifier("self".to_string())), builtin("dict", vec![]))];
 
         let self_function_defs = function_defs
             .iter()
             .map(|f| {
-                let FunctionDef(name, fun) = f;
-                let result_name;
-
-                match name {
-                    Name::DotName(_, other_names) => result_name = other_names.clone(),
-                    Name::Name(ident) => {
-                        result_name = vec![ident.clone()];
-                    }
+                let FunctionDef(name, fun, doc) = f;
+                let result_name = match name {
+                    Name::DotName(_, other_names) => other_names.clone(),
+                    Name::Name(ident) => vec![ident.clone()],
                     _ => {
                         // DO SOMETHING BAD HERE
-                        result_name = vec![Identifier("BAD".to_string())];
+                        vec![Identifier("BAD".to_string())]
                     }
-                }
+                };
 
                 FunctionDef(
                     Name::DotName(
@@ -207,6 +1279,7 @@ impl<T: Target> Compile<T> for StructDef {
                         result_name,
                     ),
                     fun.clone(),
+                    doc.clone(),
                 )
             })
             .map(|f| Expr::FunctionDef(f.clone()))
@@ -217,8 +1290,85 @@ impl<T: Target> Compile<T> for StructDef {
             "self".to_string(),
         )))));
 
-        let body = Suite(exprs);
-        let constructor: Function = Function(vec![], body);
-        Ok(Compile::<T>::compile(Expr::Assignment(name, Value::Function(constructor)))?)
+        Function(vec![], Suite(exprs), None, false)
+    }
+}
+
+impl<T: Target> Compile<T> for StructDef {
+    fn compile(self) -> Result<String, Error> {
+        let name = self.0.clone();
+        Compile::<T>::compile(Expr::Assignment(name, Value::Function(self.constructor())))
+    }
+}
+
+/// The member of an enum value that holds the name of its variant
+const ENUM_TAG: &str = "__tag__";
+/// The member of an enum, and of each of its values, that holds the name of the enum
+const ENUM_NAME: &str = "__enum__";
+const ENUM_NAMESPACE: &str = "__enum";
+
+/// A variant of an enum, with the names of its fields if it has any
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Variant(pub Identifier, pub Vec<Identifier>);
+
+/// An enum is bound to a dictionary of its variants. A variant without
+/// fields is a dictionary holding its `__tag__` and the `__enum__` it belongs
+/// to, and a variant with fields is a constructor for such a dictionary,
+/// which also holds each field both by name and by position so that it can
+/// be matched against.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub struct EnumDef(pub Name, pub Vec<Variant>);
+
+impl<T: Target> Compile<T> for EnumDef {
+    fn compile(self) -> Result<String, Error> {
+        let EnumDef(name, variants) = self;
+        let namespace = || Identifier(ENUM_NAMESPACE.to_string());
+        let instance = || Identifier("self".to_string());
+        let dot = |head: Identifier, member: &str| {
+            Name::DotName(
+                Box::new(Value::Name(Name::Name(head))),
+                vec![Identifier(member.to_string())],
+            )
+        };
+
+        let enumeration = Value::Literal(Literal::String(match &name {
+            Name::Name(Identifier(name)) => name.clone(),
+            Name::DotName(_, members) => members[members.len() - 1].0.clone(),
+            Name::IndexName(_, _) => String::new(),
+        }));
+
+        let mut exprs = vec![
+            Expr::Assignment(Name::Name(namespace()), builtin("dict", vec![])),
+            Expr::Assignment(dot(namespace(), ENUM_NAME), enumeration.clone()),
+        ];
+        for Variant(Identifier(variant), fields) in variants {
+            let mut body = vec![
+                Expr::Assignment(Name::Name(instance()), builtin("dict", vec![])),
+                Expr::Assignment(dot(instance(), ENUM_TAG), Value::Literal(Literal::String(variant.clone()))),
+                Expr::Assignment(dot(instance(), ENUM_NAME), enumeration.clone()),
+            ];
+            for (i, Identifier(field)) in fields.iter().enumerate() {
+                body.push(Expr::Assignment(dot(instance(), field), variable(field)));
+                body.push(Expr::Assignment(
+                    Name::IndexName(Box::new(Value::Name(Name::Name(instance()))), vec![number(i)]),
+                    variable(field),
+                ));
+            }
+            body.push(Expr::Value(Value::Name(Name::Name(instance()))));
+
+            let unit = fields.is_empty();
+            let parameters = fields.into_iter().map(Parameter::Name).collect();
+            let constructor = Value::Function(Function(parameters, Suite(body), None, false));
+            exprs.push(Expr::Assignment(
+                dot(namespace(), &variant),
+                if unit { Value::FnCall(FnCall(Box::new(constructor), vec![])) } else { constructor },
+            ));
+        }
+        exprs.push(Expr::Value(Value::Name(Name::Name(namespace()))));
+
+        Compile::<T>::compile(Expr::Assignment(
+            name,
+            Value::FnCall(FnCall(Box::new(Value::Function(Function(vec![], Suite(exprs), None, false))), vec![])),
+        ))
     }
 }
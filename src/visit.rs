@@ -0,0 +1,1091 @@
+use crate::{
+    Argument, Arm, Clause, Comprehension, Destructure, Element, EnumDef, Entry, Expr, FnCall, Function, FunctionDef,
+    Identifier, Literal, Name, Operator, Parameter, Pattern, Slice, StructDef, Suite, Type, Value, Variant,
+};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+
+/// Walks a syntax tree by reference. Every method defaults to visiting the
+/// children of its node, so an implementation only overrides the nodes it is
+/// interested in, calling the function of the same name in this module to
+/// keep walking below them.
+pub trait Visit {
+    fn visit_suite(&mut self, suite: &Suite) {
+        visit_suite(self, suite)
+    }
+    fn visit_expr(&mut self, expr: &Expr) {
+        visit_expr(self, expr)
+    }
+    fn visit_value(&mut self, value: &Value) {
+        visit_value(self, value)
+    }
+    fn visit_name(&mut self, name: &Name) {
+        visit_name(self, name)
+    }
+    fn visit_identifier(&mut self, _identifier: &Identifier) {}
+    fn visit_literal(&mut self, _literal: &Literal) {}
+    fn visit_operator(&mut self, _operator: &Operator) {}
+    fn visit_type(&mut self, annotation: &Type) {
+        visit_type(self, annotation)
+    }
+    fn visit_fn_call(&mut self, call: &FnCall) {
+        visit_fn_call(self, call)
+    }
+    fn visit_argument(&mut self, argument: &Argument) {
+        visit_argument(self, argument)
+    }
+    fn visit_function(&mut self, function: &Function) {
+        visit_function(self, function)
+    }
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        visit_parameter(self, parameter)
+    }
+    fn visit_function_def(&mut self, def: &FunctionDef) {
+        visit_function_def(self, def)
+    }
+    fn visit_struct_def(&mut self, def: &StructDef) {
+        visit_struct_def(self, def)
+    }
+    fn visit_enum_def(&mut self, def: &EnumDef) {
+        visit_enum_def(self, def)
+    }
+    fn visit_variant(&mut self, variant: &Variant) {
+        visit_variant(self, variant)
+    }
+    fn visit_destructure(&mut self, target: &Destructure) {
+        visit_destructure(self, target)
+    }
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        visit_pattern(self, pattern)
+    }
+    fn visit_arm(&mut self, arm: &Arm) {
+        visit_arm(self, arm)
+    }
+    fn visit_slice(&mut self, slice: &Slice) {
+        visit_slice(self, slice)
+    }
+    fn visit_element(&mut self, element: &Element) {
+        visit_element(self, element)
+    }
+    fn visit_entry(&mut self, entry: &Entry) {
+        visit_entry(self, entry)
+    }
+    fn visit_comprehension(&mut self, comprehension: &Comprehension) {
+        visit_comprehension(self, comprehension)
+    }
+    fn visit_clause(&mut self, clause: &Clause) {
+        visit_clause(self, clause)
+    }
+}
+
+pub fn visit_suite<V: Visit + ?Sized>(visitor: &mut V, suite: &Suite) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        visitor.visit_expr(expr);
+    }
+}
+
+pub fn visit_expr<V: Visit + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Assignment(name, value) => {
+            visitor.visit_name(name);
+            visitor.visit_value(value);
+        }
+        Expr::Declaration(name, annotation, value) => {
+            visitor.visit_identifier(name);
+            if let Some(annotation) = annotation {
+                visitor.visit_type(annotation);
+            }
+            visitor.visit_value(value);
+        }
+        Expr::WhileLoop(condition, body) => {
+            visitor.visit_value(condition);
+            visitor.visit_suite(body);
+        }
+        Expr::IfThenElse(condition, then_body, else_body) => {
+            visitor.visit_value(condition);
+            visitor.visit_suite(then_body);
+            visitor.visit_suite(else_body);
+        }
+        Expr::FunctionDef(def) => visitor.visit_function_def(def),
+        Expr::StructDef(def) => visitor.visit_struct_def(def),
+        Expr::EnumDef(def) => visitor.visit_enum_def(def),
+        Expr::Import(_) => {}
+        Expr::FromImport(_, names) | Expr::Global(names) | Expr::Nonlocal(names) => {
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+        }
+        Expr::Throw(value) | Expr::Yield(value) | Expr::Defer(value) | Expr::Value(value) => visitor.visit_value(value),
+        Expr::Assert(condition, message) => {
+            visitor.visit_value(condition);
+            if let Some(message) = message {
+                visitor.visit_value(message);
+            }
+        }
+        Expr::Test(_, body) => visitor.visit_suite(body),
+        Expr::TryCatch(body, handler, finally) => {
+            visitor.visit_suite(body);
+            if let Some((name, handler)) = handler {
+                visitor.visit_identifier(name);
+                visitor.visit_suite(handler);
+            }
+            visitor.visit_suite(finally);
+        }
+        Expr::With(value, name, body) => {
+            visitor.visit_value(value);
+            visitor.visit_identifier(name);
+            visitor.visit_suite(body);
+        }
+        Expr::Match(subject, arms) => {
+            visitor.visit_value(subject);
+            for arm in arms {
+                visitor.visit_arm(arm);
+            }
+        }
+        Expr::Destructure(targets, values) => {
+            for target in targets {
+                visitor.visit_destructure(target);
+            }
+            for value in values {
+                visitor.visit_value(value);
+            }
+        }
+    }
+}
+
+pub fn visit_value<V: Visit + ?Sized>(visitor: &mut V, value: &Value) {
+    match value {
+        Value::Name(name) => visitor.visit_name(name),
+        Value::Literal(literal) => visitor.visit_literal(literal),
+        Value::FnCall(call) => visitor.visit_fn_call(call),
+        Value::Function(function) => visitor.visit_function(function),
+        Value::Conditional(condition, then_value, else_value) => {
+            visitor.visit_value(condition);
+            visitor.visit_value(then_value);
+            visitor.visit_value(else_value);
+        }
+        Value::Slice(value, slice) => {
+            visitor.visit_value(value);
+            visitor.visit_slice(slice);
+        }
+        Value::List(elements) => {
+            for element in elements {
+                visitor.visit_element(element);
+            }
+        }
+        Value::Dict(entries) => {
+            for entry in entries {
+                visitor.visit_entry(entry);
+            }
+        }
+        Value::Comprehension(comprehension) => visitor.visit_comprehension(comprehension),
+        Value::Operation(left, operator, right) => {
+            visitor.visit_value(left);
+            visitor.visit_operator(operator);
+            visitor.visit_value(right);
+        }
+        Value::Await(value) => visitor.visit_value(value),
+    }
+}
+
+pub fn visit_name<V: Visit + ?Sized>(visitor: &mut V, name: &Name) {
+    match name {
+        Name::Name(name) => visitor.visit_identifier(name),
+        Name::IndexName(head, indices) => {
+            visitor.visit_value(head);
+            for index in indices {
+                visitor.visit_value(index);
+            }
+        }
+        Name::DotName(head, names) => {
+            visitor.visit_value(head);
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+        }
+    }
+}
+
+pub fn visit_type<V: Visit + ?Sized>(visitor: &mut V, annotation: &Type) {
+    let Type(name) = annotation;
+    visitor.visit_identifier(name);
+}
+
+pub fn visit_fn_call<V: Visit + ?Sized>(visitor: &mut V, call: &FnCall) {
+    let FnCall(function, arguments) = call;
+    visitor.visit_value(function);
+    for argument in arguments {
+        visitor.visit_argument(argument);
+    }
+}
+
+pub fn visit_argument<V: Visit + ?Sized>(visitor: &mut V, argument: &Argument) {
+    match argument {
+        Argument::Value(value) | Argument::Spread(value) => visitor.visit_value(value),
+        Argument::Keyword(name, value) => {
+            visitor.visit_identifier(name);
+            visitor.visit_value(value);
+        }
+    }
+}
+
+pub fn visit_function<V: Visit + ?Sized>(visitor: &mut V, function: &Function) {
+    let Function(parameters, body, returns, _) = function;
+    for parameter in parameters {
+        visitor.visit_parameter(parameter);
+    }
+    visitor.visit_suite(body);
+    if let Some(returns) = returns {
+        visitor.visit_type(returns);
+    }
+}
+
+pub fn visit_parameter<V: Visit + ?Sized>(visitor: &mut V, parameter: &Parameter) {
+    match parameter {
+        Parameter::Name(name) | Parameter::Rest(name) => visitor.visit_identifier(name),
+        Parameter::Default(name, value) => {
+            visitor.visit_identifier(name);
+            visitor.visit_value(value);
+        }
+        Parameter::Pattern(target) => visitor.visit_destructure(target),
+        Parameter::Typed(parameter, annotation) => {
+            visitor.visit_parameter(parameter);
+            visitor.visit_type(annotation);
+        }
+    }
+}
+
+pub fn visit_function_def<V: Visit + ?Sized>(visitor: &mut V, def: &FunctionDef) {
+    let FunctionDef(name, function, _) = def;
+    visitor.visit_name(name);
+    visitor.visit_function(function);
+}
+
+pub fn visit_struct_def<V: Visit + ?Sized>(visitor: &mut V, def: &StructDef) {
+    let StructDef(name, methods, _) = def;
+    visitor.visit_name(name);
+    for method in methods {
+        visitor.visit_function_def(method);
+    }
+}
+
+pub fn visit_enum_def<V: Visit + ?Sized>(visitor: &mut V, def: &EnumDef) {
+    let EnumDef(name, variants) = def;
+    visitor.visit_name(name);
+    for variant in variants {
+        visitor.visit_variant(variant);
+    }
+}
+
+pub fn visit_variant<V: Visit + ?Sized>(visitor: &mut V, variant: &Variant) {
+    let Variant(name, fields) = variant;
+    visitor.visit_identifier(name);
+    for field in fields {
+        visitor.visit_identifier(field);
+    }
+}
+
+pub fn visit_destructure<V: Visit + ?Sized>(visitor: &mut V, target: &Destructure) {
+    match target {
+        Destructure::Name(name) => visitor.visit_name(name),
+        Destructure::List(targets) => {
+            for target in targets {
+                visitor.visit_destructure(target);
+            }
+        }
+        Destructure::Dict(names) => {
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+        }
+    }
+}
+
+pub fn visit_pattern<V: Visit + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    match pattern {
+        Pattern::Wildcard => {}
+        Pattern::Binding(name) => visitor.visit_identifier(name),
+        Pattern::Literal(literal) => visitor.visit_literal(literal),
+        Pattern::Value(name) => visitor.visit_name(name),
+        Pattern::List(patterns) => {
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+        }
+        Pattern::Variant(name, patterns) => {
+            visitor.visit_name(name);
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+        }
+    }
+}
+
+pub fn visit_arm<V: Visit + ?Sized>(visitor: &mut V, arm: &Arm) {
+    let Arm(patterns, body) = arm;
+    for pattern in patterns {
+        visitor.visit_pattern(pattern);
+    }
+    visitor.visit_suite(body);
+}
+
+pub fn visit_slice<V: Visit + ?Sized>(visitor: &mut V, slice: &Slice) {
+    let Slice(start, end, step) = slice;
+    if let Some(start) = start {
+        visitor.visit_value(start);
+    }
+    if let Some(end) = end {
+        visitor.visit_value(end);
+    }
+    if let Some(step) = step {
+        visitor.visit_value(step);
+    }
+}
+
+pub fn visit_element<V: Visit + ?Sized>(visitor: &mut V, element: &Element) {
+    match element {
+        Element::Value(value) | Element::Spread(value) => visitor.visit_value(value),
+    }
+}
+
+pub fn visit_entry<V: Visit + ?Sized>(visitor: &mut V, entry: &Entry) {
+    match entry {
+        Entry::Pair(key, value) => {
+            visitor.visit_value(key);
+            visitor.visit_value(value);
+        }
+        Entry::Spread(value) => visitor.visit_value(value),
+    }
+}
+
+pub fn visit_comprehension<V: Visit + ?Sized>(visitor: &mut V, comprehension: &Comprehension) {
+    match comprehension {
+        Comprehension::List(element, clauses) => {
+            visitor.visit_value(element);
+            for clause in clauses {
+                visitor.visit_clause(clause);
+            }
+        }
+        Comprehension::Dict(key, value, clauses) => {
+            visitor.visit_value(key);
+            visitor.visit_value(value);
+            for clause in clauses {
+                visitor.visit_clause(clause);
+            }
+        }
+    }
+}
+
+pub fn visit_clause<V: Visit + ?Sized>(visitor: &mut V, clause: &Clause) {
+    match clause {
+        Clause::For(target, items) => {
+            visitor.visit_destructure(target);
+            visitor.visit_value(items);
+        }
+        Clause::If(condition) => visitor.visit_value(condition),
+    }
+}
+
+
+/// Walks a syntax tree by mutable reference, so that nodes can be rewritten
+/// in place. Like `Visit`, every method defaults to visiting the children of
+/// its node.
+pub trait VisitMut {
+    fn visit_suite_mut(&mut self, suite: &mut Suite) {
+        visit_suite_mut(self, suite)
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_expr_mut(self, expr)
+    }
+    fn visit_value_mut(&mut self, value: &mut Value) {
+        visit_value_mut(self, value)
+    }
+    fn visit_name_mut(&mut self, name: &mut Name) {
+        visit_name_mut(self, name)
+    }
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+    fn visit_literal_mut(&mut self, _literal: &mut Literal) {}
+    fn visit_operator_mut(&mut self, _operator: &mut Operator) {}
+    fn visit_type_mut(&mut self, annotation: &mut Type) {
+        visit_type_mut(self, annotation)
+    }
+    fn visit_fn_call_mut(&mut self, call: &mut FnCall) {
+        visit_fn_call_mut(self, call)
+    }
+    fn visit_argument_mut(&mut self, argument: &mut Argument) {
+        visit_argument_mut(self, argument)
+    }
+    fn visit_function_mut(&mut self, function: &mut Function) {
+        visit_function_mut(self, function)
+    }
+    fn visit_parameter_mut(&mut self, parameter: &mut Parameter) {
+        visit_parameter_mut(self, parameter)
+    }
+    fn visit_function_def_mut(&mut self, def: &mut FunctionDef) {
+        visit_function_def_mut(self, def)
+    }
+    fn visit_struct_def_mut(&mut self, def: &mut StructDef) {
+        visit_struct_def_mut(self, def)
+    }
+    fn visit_enum_def_mut(&mut self, def: &mut EnumDef) {
+        visit_enum_def_mut(self, def)
+    }
+    fn visit_variant_mut(&mut self, variant: &mut Variant) {
+        visit_variant_mut(self, variant)
+    }
+    fn visit_destructure_mut(&mut self, target: &mut Destructure) {
+        visit_destructure_mut(self, target)
+    }
+    fn visit_pattern_mut(&mut self, pattern: &mut Pattern) {
+        visit_pattern_mut(self, pattern)
+    }
+    fn visit_arm_mut(&mut self, arm: &mut Arm) {
+        visit_arm_mut(self, arm)
+    }
+    fn visit_slice_mut(&mut self, slice: &mut Slice) {
+        visit_slice_mut(self, slice)
+    }
+    fn visit_element_mut(&mut self, element: &mut Element) {
+        visit_element_mut(self, element)
+    }
+    fn visit_entry_mut(&mut self, entry: &mut Entry) {
+        visit_entry_mut(self, entry)
+    }
+    fn visit_comprehension_mut(&mut self, comprehension: &mut Comprehension) {
+        visit_comprehension_mut(self, comprehension)
+    }
+    fn visit_clause_mut(&mut self, clause: &mut Clause) {
+        visit_clause_mut(self, clause)
+    }
+}
+
+pub fn visit_suite_mut<V: VisitMut + ?Sized>(visitor: &mut V, suite: &mut Suite) {
+    let Suite(exprs) = suite;
+    for expr in exprs {
+        visitor.visit_expr_mut(expr);
+    }
+}
+
+pub fn visit_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Assignment(name, value) => {
+            visitor.visit_name_mut(name);
+            visitor.visit_value_mut(value);
+        }
+        Expr::Declaration(name, annotation, value) => {
+            visitor.visit_identifier_mut(name);
+            if let Some(annotation) = annotation {
+                visitor.visit_type_mut(annotation);
+            }
+            visitor.visit_value_mut(value);
+        }
+        Expr::WhileLoop(condition, body) => {
+            visitor.visit_value_mut(condition);
+            visitor.visit_suite_mut(body);
+        }
+        Expr::IfThenElse(condition, then_body, else_body) => {
+            visitor.visit_value_mut(condition);
+            visitor.visit_suite_mut(then_body);
+            visitor.visit_suite_mut(else_body);
+        }
+        Expr::FunctionDef(def) => visitor.visit_function_def_mut(def),
+        Expr::StructDef(def) => visitor.visit_struct_def_mut(def),
+        Expr::EnumDef(def) => visitor.visit_enum_def_mut(def),
+        Expr::Import(_) => {}
+        Expr::FromImport(_, names) | Expr::Global(names) | Expr::Nonlocal(names) => {
+            for name in names {
+                visitor.visit_identifier_mut(name);
+            }
+        }
+        Expr::Throw(value) | Expr::Yield(value) | Expr::Defer(value) | Expr::Value(value) => visitor.visit_value_mut(value),
+        Expr::Assert(condition, message) => {
+            visitor.visit_value_mut(condition);
+            if let Some(message) = message {
+                visitor.visit_value_mut(message);
+            }
+        }
+        Expr::Test(_, body) => visitor.visit_suite_mut(body),
+        Expr::TryCatch(body, handler, finally) => {
+            visitor.visit_suite_mut(body);
+            if let Some((name, handler)) = handler {
+                visitor.visit_identifier_mut(name);
+                visitor.visit_suite_mut(handler);
+            }
+            visitor.visit_suite_mut(finally);
+        }
+        Expr::With(value, name, body) => {
+            visitor.visit_value_mut(value);
+            visitor.visit_identifier_mut(name);
+            visitor.visit_suite_mut(body);
+        }
+        Expr::Match(subject, arms) => {
+            visitor.visit_value_mut(subject);
+            for arm in arms {
+                visitor.visit_arm_mut(arm);
+            }
+        }
+        Expr::Destructure(targets, values) => {
+            for target in targets {
+                visitor.visit_destructure_mut(target);
+            }
+            for value in values {
+                visitor.visit_value_mut(value);
+            }
+        }
+    }
+}
+
+pub fn visit_value_mut<V: VisitMut + ?Sized>(visitor: &mut V, value: &mut Value) {
+    match value {
+        Value::Name(name) => visitor.visit_name_mut(name),
+        Value::Literal(literal) => visitor.visit_literal_mut(literal),
+        Value::FnCall(call) => visitor.visit_fn_call_mut(call),
+        Value::Function(function) => visitor.visit_function_mut(function),
+        Value::Conditional(condition, then_value, else_value) => {
+            visitor.visit_value_mut(condition);
+            visitor.visit_value_mut(then_value);
+            visitor.visit_value_mut(else_value);
+        }
+        Value::Slice(value, slice) => {
+            visitor.visit_value_mut(value);
+            visitor.visit_slice_mut(slice);
+        }
+        Value::List(elements) => {
+            for element in elements {
+                visitor.visit_element_mut(element);
+            }
+        }
+        Value::Dict(entries) => {
+            for entry in entries {
+                visitor.visit_entry_mut(entry);
+            }
+        }
+        Value::Comprehension(comprehension) => visitor.visit_comprehension_mut(comprehension),
+        Value::Operation(left, operator, right) => {
+            visitor.visit_value_mut(left);
+            visitor.visit_operator_mut(operator);
+            visitor.visit_value_mut(right);
+        }
+        Value::Await(value) => visitor.visit_value_mut(value),
+    }
+}
+
+pub fn visit_name_mut<V: VisitMut + ?Sized>(visitor: &mut V, name: &mut Name) {
+    match name {
+        Name::Name(name) => visitor.visit_identifier_mut(name),
+        Name::IndexName(head, indices) => {
+            visitor.visit_value_mut(head);
+            for index in indices {
+                visitor.visit_value_mut(index);
+            }
+        }
+        Name::DotName(head, names) => {
+            visitor.visit_value_mut(head);
+            for name in names {
+                visitor.visit_identifier_mut(name);
+            }
+        }
+    }
+}
+
+pub fn visit_type_mut<V: VisitMut + ?Sized>(visitor: &mut V, annotation: &mut Type) {
+    let Type(name) = annotation;
+    visitor.visit_identifier_mut(name);
+}
+
+pub fn visit_fn_call_mut<V: VisitMut + ?Sized>(visitor: &mut V, call: &mut FnCall) {
+    let FnCall(function, arguments) = call;
+    visitor.visit_value_mut(function);
+    for argument in arguments {
+        visitor.visit_argument_mut(argument);
+    }
+}
+
+pub fn visit_argument_mut<V: VisitMut + ?Sized>(visitor: &mut V, argument: &mut Argument) {
+    match argument {
+        Argument::Value(value) | Argument::Spread(value) => visitor.visit_value_mut(value),
+        Argument::Keyword(name, value) => {
+            visitor.visit_identifier_mut(name);
+            visitor.visit_value_mut(value);
+        }
+    }
+}
+
+pub fn visit_function_mut<V: VisitMut + ?Sized>(visitor: &mut V, function: &mut Function) {
+    let Function(parameters, body, returns, _) = function;
+    for parameter in parameters {
+        visitor.visit_parameter_mut(parameter);
+    }
+    visitor.visit_suite_mut(body);
+    if let Some(returns) = returns {
+        visitor.visit_type_mut(returns);
+    }
+}
+
+pub fn visit_parameter_mut<V: VisitMut + ?Sized>(visitor: &mut V, parameter: &mut Parameter) {
+    match parameter {
+        Parameter::Name(name) | Parameter::Rest(name) => visitor.visit_identifier_mut(name),
+        Parameter::Default(name, value) => {
+            visitor.visit_identifier_mut(name);
+            visitor.visit_value_mut(value);
+        }
+        Parameter::Pattern(target) => visitor.visit_destructure_mut(target),
+        Parameter::Typed(parameter, annotation) => {
+            visitor.visit_parameter_mut(parameter);
+            visitor.visit_type_mut(annotation);
+        }
+    }
+}
+
+pub fn visit_function_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, def: &mut FunctionDef) {
+    let FunctionDef(name, function, _) = def;
+    visitor.visit_name_mut(name);
+    visitor.visit_function_mut(function);
+}
+
+pub fn visit_struct_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, def: &mut StructDef) {
+    let StructDef(name, methods, _) = def;
+    visitor.visit_name_mut(name);
+    for method in methods {
+        visitor.visit_function_def_mut(method);
+    }
+}
+
+pub fn visit_enum_def_mut<V: VisitMut + ?Sized>(visitor: &mut V, def: &mut EnumDef) {
+    let EnumDef(name, variants) = def;
+    visitor.visit_name_mut(name);
+    for variant in variants {
+        visitor.visit_variant_mut(variant);
+    }
+}
+
+pub fn visit_variant_mut<V: VisitMut + ?Sized>(visitor: &mut V, variant: &mut Variant) {
+    let Variant(name, fields) = variant;
+    visitor.visit_identifier_mut(name);
+    for field in fields {
+        visitor.visit_identifier_mut(field);
+    }
+}
+
+pub fn visit_destructure_mut<V: VisitMut + ?Sized>(visitor: &mut V, target: &mut Destructure) {
+    match target {
+        Destructure::Name(name) => visitor.visit_name_mut(name),
+        Destructure::List(targets) => {
+            for target in targets {
+                visitor.visit_destructure_mut(target);
+            }
+        }
+        Destructure::Dict(names) => {
+            for name in names {
+                visitor.visit_identifier_mut(name);
+            }
+        }
+    }
+}
+
+pub fn visit_pattern_mut<V: VisitMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    match pattern {
+        Pattern::Wildcard => {}
+        Pattern::Binding(name) => visitor.visit_identifier_mut(name),
+        Pattern::Literal(literal) => visitor.visit_literal_mut(literal),
+        Pattern::Value(name) => visitor.visit_name_mut(name),
+        Pattern::List(patterns) => {
+            for pattern in patterns {
+                visitor.visit_pattern_mut(pattern);
+            }
+        }
+        Pattern::Variant(name, patterns) => {
+            visitor.visit_name_mut(name);
+            for pattern in patterns {
+                visitor.visit_pattern_mut(pattern);
+            }
+        }
+    }
+}
+
+pub fn visit_arm_mut<V: VisitMut + ?Sized>(visitor: &mut V, arm: &mut Arm) {
+    let Arm(patterns, body) = arm;
+    for pattern in patterns {
+        visitor.visit_pattern_mut(pattern);
+    }
+    visitor.visit_suite_mut(body);
+}
+
+pub fn visit_slice_mut<V: VisitMut + ?Sized>(visitor: &mut V, slice: &mut Slice) {
+    let Slice(start, end, step) = slice;
+    if let Some(start) = start {
+        visitor.visit_value_mut(start);
+    }
+    if let Some(end) = end {
+        visitor.visit_value_mut(end);
+    }
+    if let Some(step) = step {
+        visitor.visit_value_mut(step);
+    }
+}
+
+pub fn visit_element_mut<V: VisitMut + ?Sized>(visitor: &mut V, element: &mut Element) {
+    match element {
+        Element::Value(value) | Element::Spread(value) => visitor.visit_value_mut(value),
+    }
+}
+
+pub fn visit_entry_mut<V: VisitMut + ?Sized>(visitor: &mut V, entry: &mut Entry) {
+    match entry {
+        Entry::Pair(key, value) => {
+            visitor.visit_value_mut(key);
+            visitor.visit_value_mut(value);
+        }
+        Entry::Spread(value) => visitor.visit_value_mut(value),
+    }
+}
+
+pub fn visit_comprehension_mut<V: VisitMut + ?Sized>(visitor: &mut V, comprehension: &mut Comprehension) {
+    match comprehension {
+        Comprehension::List(element, clauses) => {
+            visitor.visit_value_mut(element);
+            for clause in clauses {
+                visitor.visit_clause_mut(clause);
+            }
+        }
+        Comprehension::Dict(key, value, clauses) => {
+            visitor.visit_value_mut(key);
+            visitor.visit_value_mut(value);
+            for clause in clauses {
+                visitor.visit_clause_mut(clause);
+            }
+        }
+    }
+}
+
+pub fn visit_clause_mut<V: VisitMut + ?Sized>(visitor: &mut V, clause: &mut Clause) {
+    match clause {
+        Clause::For(target, items) => {
+            visitor.visit_destructure_mut(target);
+            visitor.visit_value_mut(items);
+        }
+        Clause::If(condition) => visitor.visit_value_mut(condition),
+    }
+}
+
+
+/// Rebuilds a syntax tree by value, so that nodes can be replaced with nodes
+/// of a different shape. Every method defaults to folding the children of its
+/// node and putting it back together.
+pub trait Fold {
+    fn fold_suite(&mut self, suite: Suite) -> Suite {
+        fold_suite(self, suite)
+    }
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        fold_expr(self, expr)
+    }
+    fn fold_value(&mut self, value: Value) -> Value {
+        fold_value(self, value)
+    }
+    fn fold_name(&mut self, name: Name) -> Name {
+        fold_name(self, name)
+    }
+    fn fold_identifier(&mut self, identifier: Identifier) -> Identifier {
+        identifier
+    }
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        literal
+    }
+    fn fold_operator(&mut self, operator: Operator) -> Operator {
+        operator
+    }
+    fn fold_type(&mut self, annotation: Type) -> Type {
+        fold_type(self, annotation)
+    }
+    fn fold_fn_call(&mut self, call: FnCall) -> FnCall {
+        fold_fn_call(self, call)
+    }
+    fn fold_argument(&mut self, argument: Argument) -> Argument {
+        fold_argument(self, argument)
+    }
+    fn fold_function(&mut self, function: Function) -> Function {
+        fold_function(self, function)
+    }
+    fn fold_parameter(&mut self, parameter: Parameter) -> Parameter {
+        fold_parameter(self, parameter)
+    }
+    fn fold_function_def(&mut self, def: FunctionDef) -> FunctionDef {
+        fold_function_def(self, def)
+    }
+    fn fold_struct_def(&mut self, def: StructDef) -> StructDef {
+        fold_struct_def(self, def)
+    }
+    fn fold_enum_def(&mut self, def: EnumDef) -> EnumDef {
+        fold_enum_def(self, def)
+    }
+    fn fold_variant(&mut self, variant: Variant) -> Variant {
+        fold_variant(self, variant)
+    }
+    fn fold_destructure(&mut self, target: Destructure) -> Destructure {
+        fold_destructure(self, target)
+    }
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        fold_pattern(self, pattern)
+    }
+    fn fold_arm(&mut self, arm: Arm) -> Arm {
+        fold_arm(self, arm)
+    }
+    fn fold_slice(&mut self, slice: Slice) -> Slice {
+        fold_slice(self, slice)
+    }
+    fn fold_element(&mut self, element: Element) -> Element {
+        fold_element(self, element)
+    }
+    fn fold_entry(&mut self, entry: Entry) -> Entry {
+        fold_entry(self, entry)
+    }
+    fn fold_comprehension(&mut self, comprehension: Comprehension) -> Comprehension {
+        fold_comprehension(self, comprehension)
+    }
+    fn fold_clause(&mut self, clause: Clause) -> Clause {
+        fold_clause(self, clause)
+    }
+}
+
+pub fn fold_suite<F: Fold + ?Sized>(folder: &mut F, suite: Suite) -> Suite {
+    let Suite(exprs) = suite;
+    Suite(exprs.into_iter().map(|expr| folder.fold_expr(expr)).collect())
+}
+
+pub fn fold_expr<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Expr {
+    match expr {
+        Expr::Assignment(name, value) => Expr::Assignment(folder.fold_name(name), folder.fold_value(value)),
+        Expr::Declaration(name, annotation, value) => Expr::Declaration(
+            folder.fold_identifier(name),
+            annotation.map(|annotation| folder.fold_type(annotation)),
+            folder.fold_value(value),
+        ),
+        Expr::WhileLoop(condition, body) => Expr::WhileLoop(folder.fold_value(condition), folder.fold_suite(body)),
+        Expr::IfThenElse(condition, then_body, else_body) => Expr::IfThenElse(
+            folder.fold_value(condition),
+            folder.fold_suite(then_body),
+            folder.fold_suite(else_body),
+        ),
+        Expr::FunctionDef(def) => Expr::FunctionDef(folder.fold_function_def(def)),
+        Expr::StructDef(def) => Expr::StructDef(folder.fold_struct_def(def)),
+        Expr::EnumDef(def) => Expr::EnumDef(folder.fold_enum_def(def)),
+        Expr::Import(path) => Expr::Import(path),
+        Expr::FromImport(path, names) => Expr::FromImport(path, fold_identifiers(folder, names)),
+        Expr::Throw(value) => Expr::Throw(folder.fold_value(value)),
+        Expr::Assert(condition, message) => {
+            Expr::Assert(folder.fold_value(condition), message.map(|message| folder.fold_value(message)))
+        }
+        Expr::Test(name, body) => Expr::Test(name, folder.fold_suite(body)),
+        Expr::Yield(value) => Expr::Yield(folder.fold_value(value)),
+        Expr::TryCatch(body, handler, finally) => Expr::TryCatch(
+            folder.fold_suite(body),
+            handler.map(|(name, handler)| (folder.fold_identifier(name), folder.fold_suite(handler))),
+            folder.fold_suite(finally),
+        ),
+        Expr::Global(names) => Expr::Global(fold_identifiers(folder, names)),
+        Expr::Nonlocal(names) => Expr::Nonlocal(fold_identifiers(folder, names)),
+        Expr::Defer(value) => Expr::Defer(folder.fold_value(value)),
+        Expr::With(value, name, body) => {
+            Expr::With(folder.fold_value(value), folder.fold_identifier(name), folder.fold_suite(body))
+        }
+        Expr::Match(subject, arms) => Expr::Match(
+            folder.fold_value(subject),
+            arms.into_iter().map(|arm| folder.fold_arm(arm)).collect(),
+        ),
+        Expr::Destructure(targets, values) => Expr::Destructure(
+            targets.into_iter().map(|target| folder.fold_destructure(target)).collect(),
+            fold_values(folder, values),
+        ),
+        Expr::Value(value) => Expr::Value(folder.fold_value(value)),
+    }
+}
+
+pub fn fold_value<F: Fold + ?Sized>(folder: &mut F, value: Value) -> Value {
+    match value {
+        Value::Name(name) => Value::Name(folder.fold_name(name)),
+        Value::Literal(literal) => Value::Literal(folder.fold_literal(literal)),
+        Value::FnCall(call) => Value::FnCall(folder.fold_fn_call(call)),
+        Value::Function(function) => Value::Function(folder.fold_function(function)),
+        Value::Conditional(condition, then_value, else_value) => Value::Conditional(
+            Box::new(folder.fold_value(*condition)),
+            Box::new(folder.fold_value(*then_value)),
+            Box::new(folder.fold_value(*else_value)),
+        ),
+        Value::Slice(value, slice) => Value::Slice(Box::new(folder.fold_value(*value)), folder.fold_slice(slice)),
+        Value::List(elements) => Value::List(elements.into_iter().map(|element| folder.fold_element(element)).collect()),
+        Value::Dict(entries) => Value::Dict(entries.into_iter().map(|entry| folder.fold_entry(entry)).collect()),
+        Value::Comprehension(comprehension) => {
+            Value::Comprehension(Box::new(folder.fold_comprehension(*comprehension)))
+        }
+        Value::Operation(left, operator, right) => {
+            let left = Box::new(folder.fold_value(*left));
+            Value::Operation(left, folder.fold_operator(operator), Box::new(folder.fold_value(*right)))
+        }
+        Value::Await(value) => Value::Await(Box::new(folder.fold_value(*value))),
+    }
+}
+
+pub fn fold_name<F: Fold + ?Sized>(folder: &mut F, name: Name) -> Name {
+    match name {
+        Name::Name(name) => Name::Name(folder.fold_identifier(name)),
+        Name::IndexName(head, indices) => Name::IndexName(Box::new(folder.fold_value(*head)), fold_values(folder, indices)),
+        Name::DotName(head, names) => Name::DotName(Box::new(folder.fold_value(*head)), fold_identifiers(folder, names)),
+    }
+}
+
+pub fn fold_type<F: Fold + ?Sized>(folder: &mut F, annotation: Type) -> Type {
+    let Type(name) = annotation;
+    Type(folder.fold_identifier(name))
+}
+
+pub fn fold_fn_call<F: Fold + ?Sized>(folder: &mut F, call: FnCall) -> FnCall {
+    let FnCall(function, arguments) = call;
+    FnCall(
+        Box::new(folder.fold_value(*function)),
+        arguments.into_iter().map(|argument| folder.fold_argument(argument)).collect(),
+    )
+}
+
+pub fn fold_argument<F: Fold + ?Sized>(folder: &mut F, argument: Argument) -> Argument {
+    match argument {
+        Argument::Value(value) => Argument::Value(folder.fold_value(value)),
+        Argument::Spread(value) => Argument::Spread(folder.fold_value(value)),
+        Argument::Keyword(name, value) => Argument::Keyword(folder.fold_identifier(name), folder.fold_value(value)),
+    }
+}
+
+pub fn fold_function<F: Fold + ?Sized>(folder: &mut F, function: Function) -> Function {
+    let Function(parameters, body, returns, asynchronous) = function;
+    Function(
+        parameters.into_iter().map(|parameter| folder.fold_parameter(parameter)).collect(),
+        folder.fold_suite(body),
+        returns.map(|returns| folder.fold_type(returns)),
+        asynchronous,
+    )
+}
+
+pub fn fold_parameter<F: Fold + ?Sized>(folder: &mut F, parameter: Parameter) -> Parameter {
+    match parameter {
+        Parameter::Name(name) => Parameter::Name(folder.fold_identifier(name)),
+        Parameter::Default(name, value) => Parameter::Default(folder.fold_identifier(name), folder.fold_value(value)),
+        Parameter::Rest(name) => Parameter::Rest(folder.fold_identifier(name)),
+        Parameter::Pattern(target) => Parameter::Pattern(folder.fold_destructure(target)),
+        Parameter::Typed(parameter, annotation) => {
+            Parameter::Typed(Box::new(folder.fold_parameter(*parameter)), folder.fold_type(annotation))
+        }
+    }
+}
+
+pub fn fold_function_def<F: Fold + ?Sized>(folder: &mut F, def: FunctionDef) -> FunctionDef {
+    let FunctionDef(name, function, doc) = def;
+    FunctionDef(folder.fold_name(name), folder.fold_function(function), doc)
+}
+
+pub fn fold_struct_def<F: Fold + ?Sized>(folder: &mut F, def: StructDef) -> StructDef {
+    let StructDef(name, methods, doc) = def;
+    StructDef(
+        folder.fold_name(name),
+        methods.into_iter().map(|method| folder.fold_function_def(method)).collect(),
+        doc,
+    )
+}
+
+pub fn fold_enum_def<F: Fold + ?Sized>(folder: &mut F, def: EnumDef) -> EnumDef {
+    let EnumDef(name, variants) = def;
+    EnumDef(
+        folder.fold_name(name),
+        variants.into_iter().map(|variant| folder.fold_variant(variant)).collect(),
+    )
+}
+
+pub fn fold_variant<F: Fold + ?Sized>(folder: &mut F, variant: Variant) -> Variant {
+    let Variant(name, fields) = variant;
+    Variant(folder.fold_identifier(name), fold_identifiers(folder, fields))
+}
+
+pub fn fold_destructure<F: Fold + ?Sized>(folder: &mut F, target: Destructure) -> Destructure {
+    match target {
+        Destructure::Name(name) => Destructure::Name(folder.fold_name(name)),
+        Destructure::List(targets) => {
+            Destructure::List(targets.into_iter().map(|target| folder.fold_destructure(target)).collect())
+        }
+        Destructure::Dict(names) => Destructure::Dict(fold_identifiers(folder, names)),
+    }
+}
+
+pub fn fold_pattern<F: Fold + ?Sized>(folder: &mut F, pattern: Pattern) -> Pattern {
+    match pattern {
+        Pattern::Wildcard => Pattern::Wildcard,
+        Pattern::Binding(name) => Pattern::Binding(folder.fold_identifier(name)),
+        Pattern::Literal(literal) => Pattern::Literal(folder.fold_literal(literal)),
+        Pattern::Value(name) => Pattern::Value(folder.fold_name(name)),
+        Pattern::List(patterns) => Pattern::List(fold_patterns(folder, patterns)),
+        Pattern::Variant(name, patterns) => Pattern::Variant(folder.fold_name(name), fold_patterns(folder, patterns)),
+    }
+}
+
+pub fn fold_arm<F: Fold + ?Sized>(folder: &mut F, arm: Arm) -> Arm {
+    let Arm(patterns, body) = arm;
+    Arm(fold_patterns(folder, patterns), folder.fold_suite(body))
+}
+
+pub fn fold_slice<F: Fold + ?Sized>(folder: &mut F, slice: Slice) -> Slice {
+    let Slice(start, end, step) = slice;
+    Slice(
+        start.map(|start| Box::new(folder.fold_value(*start))),
+        end.map(|end| Box::new(folder.fold_value(*end))),
+        step.map(|step| Box::new(folder.fold_value(*step))),
+    )
+}
+
+pub fn fold_element<F: Fold + ?Sized>(folder: &mut F, element: Element) -> Element {
+    match element {
+        Element::Value(value) => Element::Value(folder.fold_value(value)),
+        Element::Spread(value) => Element::Spread(folder.fold_value(value)),
+    }
+}
+
+pub fn fold_entry<F: Fold + ?Sized>(folder: &mut F, entry: Entry) -> Entry {
+    match entry {
+        Entry::Pair(key, value) => Entry::Pair(folder.fold_value(key), folder.fold_value(value)),
+        Entry::Spread(value) => Entry::Spread(folder.fold_value(value)),
+    }
+}
+
+pub fn fold_comprehension<F: Fold + ?Sized>(folder: &mut F, comprehension: Comprehension) -> Comprehension {
+    match comprehension {
+        Comprehension::List(element, clauses) => {
+            Comprehension::List(folder.fold_value(element), fold_clauses(folder, clauses))
+        }
+        Comprehension::Dict(key, value, clauses) => {
+            let key = folder.fold_value(key);
+            Comprehension::Dict(key, folder.fold_value(value), fold_clauses(folder, clauses))
+        }
+    }
+}
+
+pub fn fold_clause<F: Fold + ?Sized>(folder: &mut F, clause: Clause) -> Clause {
+    match clause {
+        Clause::For(target, items) => Clause::For(folder.fold_destructure(target), folder.fold_value(items)),
+        Clause::If(condition) => Clause::If(folder.fold_value(condition)),
+    }
+}
+
+fn fold_values<F: Fold + ?Sized>(folder: &mut F, values: Vec<Value>) -> Vec<Value> {
+    values.into_iter().map(|value| folder.fold_value(value)).collect()
+}
+
+fn fold_identifiers<F: Fold + ?Sized>(folder: &mut F, names: Vec<Identifier>) -> Vec<Identifier> {
+    names.into_iter().map(|name| folder.fold_identifier(name)).collect()
+}
+
+fn fold_patterns<F: Fold + ?Sized>(folder: &mut F, patterns: Vec<Pattern>) -> Vec<Pattern> {
+    patterns.into_iter().map(|pattern| folder.fold_pattern(pattern)).collect()
+}
+
+fn fold_clauses<F: Fold + ?Sized>(folder: &mut F, clauses: Vec<Clause>) -> Vec<Clause> {
+    clauses.into_iter().map(|clause| folder.fold_clause(clause)).collect()
+}
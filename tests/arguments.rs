@@ -0,0 +1,55 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Argument, Expr, Function, FunctionDef, Identifier, Literal, Name, Parameter, Suite, Value, Golang, Rust};
+
+mod common;
+use common::name;
+
+#[test]
+fn parameters_test() {
+    assert_eq!(
+        parse("fn f(a, b = 2, ...rest) {}").unwrap().0,
+        vec![Expr::FunctionDef(FunctionDef(
+            Name::Name(Identifier("f".to_string())),
+            Function(
+                vec![
+                    Parameter::Name(Identifier("a".to_string())),
+                    Parameter::Default(
+                        Identifier("b".to_string()),
+                        Value::Literal(Literal::Number("2".to_string()))
+                    ),
+                    Parameter::Rest(Identifier("rest".to_string())),
+                ],
+                Suite(vec![]),
+                None,
+                false
+            ),
+            None
+        ))]
+    );
+
+    let code = compile::<Rust>("fn f(a, b = 2, ...rest) {}").unwrap();
+    assert!(code.contains(r#"xasm.arguments(&["a", "b"], 1, true)?;"#));
+    assert!(compile::<Golang>("fn f(a) {}").unwrap().contains(r#"xasm.Arguments([]string{"a"}, 0, false)"#));
+
+    assert!(compile::<Rust>("fn f(a = 1, b) {}").is_err());
+    assert!(compile::<Rust>("fn f(...a, b) {}").is_err());
+}
+
+#[test]
+fn spread_test() {
+    match &parse("f(a, ...xs)").unwrap().0[0] {
+        Expr::Value(Value::FnCall(call)) => assert_eq!(
+            call.1,
+            vec![Argument::Value(name("a")), Argument::Spread(name("xs"))]
+        ),
+        _ => panic!("expected a call"),
+    }
+
+    let plain = compile::<Rust>("f(a, b)").unwrap();
+    assert!(plain.contains("xasm.push(Value::number(2));"));
+    assert!(!plain.contains("xasm.argument();"));
+
+    let spread = compile::<Rust>("f(a, ...xs)").unwrap();
+    assert!(spread.contains("xasm.push(Value::number(0));"));
+    assert!(spread.find("xasm.spread()?;").unwrap() < spread.find("xasm.argument();").unwrap());
+}
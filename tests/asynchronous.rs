@@ -0,0 +1,63 @@
+extern crate xassembler;
+use xassembler::{check, compile, parse, Error, Expr, FnCall, Function, FunctionDef, Golang, Identifier, Name, Rust, Value};
+
+mod common;
+use common::value;
+
+#[test]
+fn async_test() {
+    match &parse("async fn fetch(url) { await get(url) }").unwrap().0[0] {
+        Expr::FunctionDef(FunctionDef(Name::Name(Identifier(name)), Function(_, body, _, asynchronous), _)) => {
+            assert_eq!(name, "fetch");
+            assert!(asynchronous);
+            assert!(matches!(body.0[0], Expr::Value(Value::Await(_))));
+        }
+        _ => panic!("expected a function definition"),
+    }
+    assert!(matches!(
+        &parse("f = async fn(x) => await x").unwrap().0[0],
+        Expr::Assignment(_, Value::Function(Function(_, _, _, true)))
+    ));
+    assert!(matches!(
+        &parse("fn f() {}").unwrap().0[0],
+        Expr::FunctionDef(FunctionDef(_, Function(_, _, _, false), _))
+    ));
+    assert!(parse("asynchronous = awaited").is_ok());
+
+    let code = compile::<Rust>("async fn f(url) { page = await get(url) page }").unwrap();
+    assert!(code.contains("Value::future(|xasm: &mut Machine| -> Result<(), Value> {"));
+    assert!(code.contains("xasm.await_value()?;"));
+    let code = compile::<Golang>("async fn f(url) { await get(url) }").unwrap();
+    assert!(code.contains("NewAsyncFunction(func(xasm *Machine) {"));
+    assert!(code.contains("xasm.Await()"));
+}
+
+#[test]
+fn async_errors_test() {
+    assert_eq!(check(&parse("await f()").unwrap()), Err(Error::AwaitOutsideAsync));
+    assert_eq!(
+        check(&parse("async fn f() { g = fn() { await h() } }").unwrap()),
+        Err(Error::AwaitOutsideAsync)
+    );
+    assert_eq!(compile::<Rust>("async fn f() { yield 1 }"), Err("AsyncGenerator".to_string()));
+
+    // a collection or operation built by a function of its own can await in an asynchronous function
+    for script in &[
+        "async fn f(xs) { [await g(x) for x in xs] }",
+        r#"async fn f(x) { {"a": await g(x)} }"#,
+        "async fn f(x) { a.b() + await g(x) }",
+    ] {
+        assert_eq!(check(&parse(script).unwrap()), Ok(vec![]));
+        assert!(compile::<Rust>(script).is_ok());
+    }
+    assert_eq!(check(&parse(r#"fn f(x) { {"a": await g(x)} }"#).unwrap()), Err(Error::AwaitOutsideAsync));
+    // that function is asynchronous, and its result is awaited in turn
+    match value("[await g(x) for x in xs]").desugar() {
+        Value::Await(call) => match *call {
+            Value::FnCall(FnCall(function, _)) => assert!(matches!(*function, Value::Function(Function(_, _, _, true)))),
+            _ => panic!("expected a call"),
+        },
+        _ => panic!("expected the result to be awaited"),
+    }
+    assert!(matches!(value("[g(x) for x in xs]").desugar(), Value::FnCall(_)));
+}
@@ -0,0 +1,43 @@
+extern crate xassembler;
+use xassembler::{compile, free_variables, parse, Expr, FunctionDef, Golang, Identifier, Rust};
+
+fn free(script: &str) -> Vec<String> {
+    match &parse(script).unwrap().0[0] {
+        Expr::FunctionDef(FunctionDef(_, function, _)) => free_variables(function)
+            .into_iter()
+            .map(|Identifier(name)| name)
+            .collect(),
+        _ => panic!("expected a function definition"),
+    }
+}
+
+#[test]
+fn free_variables_test() {
+    assert_eq!(free("fn f(a, b = c) { d = a; e(b, d) }"), vec!["c", "e"]);
+    assert_eq!(free("fn f() { g = fn(x) { x.add(y) } }"), vec!["y"]);
+    assert_eq!(free("fn f([a, b], {c}) { a.add(b).add(c) }"), Vec::<String>::new());
+    assert_eq!(free("fn f() { match x { [y] => y, z => w } }"), vec!["w", "x"]);
+    assert_eq!(free("fn f(v) { match v { Color.Rgb(r, g, b) => r, _ => 0 } }"), vec!["Color"]);
+    assert_eq!(free("fn f(v) { match v { shapes.Shape.Circle(r) => r, Rgb(r) => r } }"), vec!["shapes"]);
+    assert_eq!(free("fn f() { try { a() } catch e { b(e) } }"), vec!["a", "b"]);
+    assert_eq!(free(r#"fn f() { import "m" m.g() }"#), vec!["__module__m"]);
+    assert_eq!(free("fn f() { class A { fn g() { self.h(k) } } A() }"), vec!["k"]);
+    assert_eq!(free("fn f() { x.y = z }"), vec!["x", "z"]);
+    // only names declared nonlocal are shared for writing
+    assert_eq!(free("fn f() { n = n.add(1) }"), Vec::<String>::new());
+    assert_eq!(free("fn f() { nonlocal n; n = n.add(1) }"), vec!["n"]);
+}
+
+#[test]
+fn capture_test() {
+    let code = compile::<Rust>("counter = 0\nfn count() { counter.add(1) }").unwrap();
+    assert!(code.contains(r#"xasm.capture(&["counter"]);"#));
+
+    // functions that refer to nothing outside themselves are left alone
+    assert!(!compile::<Rust>("fn f(x) { x }").unwrap().contains("capture"));
+    assert!(compile::<Golang>("fn f() { g() }").unwrap().contains(r#"xasm.Capture([]string{"g"})"#));
+
+    // the enum a variant pattern is matched by is captured
+    let code = compile::<Rust>("enum Color { Rgb(r, g, b) }\nfn red(v) { match v { Color.Rgb(r, g, b) => r, _ => 0 } }").unwrap();
+    assert!(code.contains(r#"xasm.capture(&["Color"]);"#));
+}
@@ -0,0 +1,144 @@
+extern crate xassembler;
+use xassembler::{
+    check, free_variables, parse, Clause, Compile, Comprehension, Destructure, Element, Entry, Error, Expr, FunctionDef,
+    Golang, Identifier, Literal, Name, Rust, Type, Value,
+};
+
+mod common;
+use common::{builtin_value, name, number, value};
+
+/// The builtins that building collections calls
+const BUILTINS: &[&str] = &["list", "dict", "less", "len", "add"];
+
+#[test]
+fn literal_test() {
+    assert_eq!(
+        value("[1, ...rest, x]"),
+        Value::List(vec![
+            Element::Value(number("1")),
+            Element::Spread(name("rest")),
+            Element::Value(name("x")),
+        ])
+    );
+    assert_eq!(value("[]"), Value::List(vec![]));
+    assert_eq!(
+        value(r#"{"a": 1, ...defaults,}"#),
+        Value::Dict(vec![
+            Entry::Pair(Value::Literal(Literal::String("a".to_string())), number("1")),
+            Entry::Spread(name("defaults")),
+        ])
+    );
+
+    // destructuring is still preferred at the start of a statement
+    assert!(matches!(parse("[a, b] = pair").unwrap().0[0], Expr::Destructure(_, _)));
+    assert!(matches!(parse("{a, b} = pair").unwrap().0[0], Expr::Destructure(_, _)));
+
+    // a list is built by a call, and a dictionary by a function that adds each entry in turn
+    assert_eq!(value("[1, ...rest]").desugar(), builtin_value("list(1, ...rest)", &["list"]));
+    assert_eq!(
+        Compile::<Rust>::compile(value(r#"{"a": 1, ...defaults}"#).desugar()),
+        Compile::<Rust>::compile(builtin_value(
+            r#"
+(fn() {
+    __collection = dict()
+    __collection["a"] = 1
+    __spread = defaults
+    __items0 = __spread.keys()
+    __index0 = 0
+    while less(__index0, len(__items0)) {
+        __key = __items0[__index0]
+        __collection[__key] = __spread[__key]
+        __index0 = add(__index0, 1)
+    }
+    __collection
+})()
+"#,
+            BUILTINS
+        ))
+    );
+
+    // a literal can be indexed, sliced, called or have its members looked up
+    assert_eq!(
+        parse("x = [1, 2][0]").unwrap().0,
+        vec![Expr::Assignment(
+            Name::Name(Identifier("x".to_string())),
+            Value::Name(Name::IndexName(Box::new(value("[1, 2]")), vec![number("0")]))
+        )]
+    );
+    assert_eq!(value(r#"{"a": 1}.a"#), Value::Name(Name::DotName(Box::new(value(r#"{"a": 1}"#)), vec![Identifier("a".to_string())])));
+    assert!(matches!(value("[x for x in xs][1:]"), Value::Slice(_, _)));
+    assert!(matches!(value("[1, 2].len()"), Value::FnCall(_)));
+}
+
+#[test]
+fn comprehension_test() {
+    assert_eq!(
+        value("[f(x) for x in xs if p(x)]"),
+        Value::Comprehension(Box::new(Comprehension::List(
+            value("f(x)"),
+            vec![
+                Clause::For(Destructure::Name(Name::Name(Identifier("x".to_string()))), name("xs")),
+                Clause::If(value("p(x)")),
+            ]
+        )))
+    );
+    assert!(matches!(
+        value("{k: v for [k, v] in pairs for _ in once}"),
+        Value::Comprehension(c) if matches!(&*c, Comprehension::Dict(_, _, clauses) if clauses.len() == 2)
+    ));
+    // keywords can still begin names
+    assert!(parse("format = index").is_ok());
+
+    // the clauses are nested loops and conditions, in the order they are written
+    assert_eq!(
+        Compile::<Rust>::compile(value("[f(x) for x in xs if p(x)]").desugar()),
+        Compile::<Rust>::compile(builtin_value(
+            r#"
+(fn() {
+    __collection = list()
+    __items0 = xs
+    __index0 = 0
+    while less(__index0, len(__items0)) {
+        x = __items0[__index0]
+        if p(x) { __collection.push(f(x)) }
+        __index0 = add(__index0, 1)
+    }
+    __collection
+})()
+"#,
+            BUILTINS
+        ))
+    );
+    assert_eq!(
+        Compile::<Golang>::compile(value("{x: 1 for x in xs}").desugar()),
+        Compile::<Golang>::compile(builtin_value(
+            r#"
+(fn() {
+    __collection = dict()
+    __items0 = xs
+    __index0 = 0
+    while less(__index0, len(__items0)) {
+        x = __items0[__index0]
+        __collection[x] = 1
+        __index0 = add(__index0, 1)
+    }
+    __collection
+})()
+"#,
+            BUILTINS
+        ))
+    );
+
+    // the names a comprehension binds don't escape it
+    match &parse("fn f() { [x for x in xs] }").unwrap().0[0] {
+        Expr::FunctionDef(FunctionDef(_, function, _)) => {
+            let free = free_variables(function);
+            assert!(free.contains(&Identifier("xs".to_string())));
+            assert!(!free.contains(&Identifier("x".to_string())));
+        }
+        _ => panic!("expected a function definition"),
+    }
+
+    let ast = parse("let xs: list = [x for x in ys]\nlet n: num = [1]").unwrap();
+    assert_eq!(check(&ast), Err(Error::TypeMismatch("n".to_string(), Type::new("num"), Type::new("list"))));
+}
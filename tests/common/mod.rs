@@ -0,0 +1,53 @@
+//! Helpers for building the syntax trees the tests compare against
+#![allow(dead_code)]
+
+use xassembler::visit::{self, VisitMut};
+use xassembler::{parse, Expr, Identifier, Literal, Name, Value};
+
+pub fn ident(n: &str) -> Identifier {
+    Identifier(n.to_string())
+}
+
+pub fn name(n: &str) -> Value {
+    Value::Name(Name::Name(ident(n)))
+}
+
+pub fn number(n: &str) -> Value {
+    Value::Literal(Literal::Number(n.to_string()))
+}
+
+/// The value of a script made of a single expression
+pub fn value(script: &str) -> Value {
+    match parse(script).unwrap().0.remove(0) {
+        Expr::Value(value) => value,
+        _ => panic!("expected a value"),
+    }
+}
+
+/// Renames each of the names it is given to the builtin of that name
+struct Builtins<'a>(&'a [&'a str]);
+
+impl VisitMut for Builtins<'_> {
+    fn visit_name_mut(&mut self, name: &mut Name) {
+        match name {
+            Name::Name(Identifier(n)) if self.0.contains(&n.as_str()) => *name = Name::Name(Identifier::builtin(n)),
+            _ => visit::visit_name_mut(self, name),
+        }
+    }
+}
+
+/// The statements of a script in which `builtins` call the builtins of
+/// those names, as they do in the code lowerings generate
+pub fn with_builtins(script: &str, builtins: &[&str]) -> Vec<Expr> {
+    let mut suite = parse(script).unwrap();
+    Builtins(builtins).visit_suite_mut(&mut suite);
+    suite.0
+}
+
+/// Like `value`, but `builtins` call the builtins of those names
+pub fn builtin_value(script: &str, builtins: &[&str]) -> Value {
+    match with_builtins(script, builtins).remove(0) {
+        Expr::Value(value) => value,
+        _ => panic!("expected a value"),
+    }
+}
@@ -0,0 +1,42 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Argument, Expr, FnCall, Golang, Identifier, Name, Rust, Value};
+
+mod common;
+use common::name;
+
+#[test]
+fn conditional_test() {
+    assert_eq!(
+        parse("x = if c { a } else if d { b } else { e }").unwrap().0,
+        vec![Expr::Assignment(
+            Name::Name(Identifier("x".to_string())),
+            Value::Conditional(
+                Box::new(name("c")),
+                Box::new(name("a")),
+                Box::new(Value::Conditional(
+                    Box::new(name("d")),
+                    Box::new(name("b")),
+                    Box::new(name("e"))
+                ))
+            )
+        )]
+    );
+
+    assert_eq!(
+        parse("f(if c { a } else { b })").unwrap().0,
+        vec![Expr::Value(Value::FnCall(FnCall(
+            Box::new(name("f")),
+            vec![Argument::Value(Value::Conditional(
+                Box::new(name("c")),
+                Box::new(name("a")),
+                Box::new(name("b"))
+            ))]
+        )))]
+    );
+}
+
+#[test]
+fn conditional_compile_test() {
+    assert!(compile::<Rust>("x = if c { a } else { b }").unwrap().contains("xasm.if_then_else()?;"));
+    assert!(compile::<Golang>("f(if c { a } else { b })").unwrap().contains("xasm.IfThenElse()"));
+}
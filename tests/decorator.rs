@@ -0,0 +1,58 @@
+extern crate xassembler;
+use xassembler::{check, compile, parse, Argument, Expr, FnCall, Identifier, Literal, Name, Rust, Value};
+
+mod common;
+use common::name;
+
+/// The callee of each decorator applied to a definition, outermost first
+fn decorators(mut value: &Value) -> Vec<Value> {
+    let mut result = vec![];
+    while let Value::FnCall(FnCall(decorator, arguments)) = value {
+        result.push((**decorator).clone());
+        match &arguments[..] {
+            [.., Argument::Value(inner)] => value = inner,
+            _ => break,
+        }
+    }
+    result
+}
+
+#[test]
+fn decorator_test() {
+    // the definition is followed by the assignment of its decorated value to its name
+    let ast = parse("@log @cache(10) fn f(x) { x }").unwrap();
+    assert_eq!(ast.0[0], parse("fn f(x) { x }").unwrap().0[0]);
+    match &ast.0[1] {
+        Expr::Assignment(Name::Name(Identifier(n)), value) => {
+            assert_eq!(n, "f");
+            let applied = decorators(value);
+            assert_eq!(applied[0], name("log"));
+            assert!(matches!(
+                &applied[1],
+                Value::FnCall(FnCall(cache, arguments))
+                    if **cache == name("cache")
+                    && arguments == &vec![Argument::Value(Value::Literal(Literal::Number("10".to_string())))]
+            ));
+        }
+        _ => panic!("expected an assignment"),
+    }
+    assert_eq!(ast.0.len(), 2);
+
+    let ast = parse("@registry.add class Shape { fn area() { 0 } }").unwrap();
+    assert!(matches!(&ast.0[0], Expr::StructDef(_)));
+    assert!(matches!(&ast.0[1], Expr::Assignment(Name::Name(_), Value::FnCall(_))));
+
+    // a foreign function literal on its own is still a value
+    assert!(matches!(
+        parse("@println").unwrap().0[0],
+        Expr::Value(Value::Literal(Literal::ForeignFunction(_)))
+    ));
+
+    let ast = parse("@log fn f(x) { x }\nf(y = 1)").unwrap();
+    // a decorated function may not take the parameters it was defined with
+    assert_eq!(check(&ast), Ok(vec![]));
+
+    let code = compile::<Rust>("@log fn f(x) { x }").unwrap();
+    assert!(code.contains(r#"xasm.push(Value::string("log"));"#));
+    assert!(code.contains(r#"xasm.push(Value::string("f"));"#));
+}
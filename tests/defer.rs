@@ -0,0 +1,65 @@
+extern crate xassembler;
+use xassembler::{check, compile, compile_tests, parse, Error, Expr, Golang, Identifier, Name, Rust, Suite, Value};
+
+mod common;
+use common::{name, value};
+
+#[test]
+fn defer_test() {
+    match &parse("fn f() { defer close(a) a.write(1) }").unwrap().0[0] {
+        Expr::FunctionDef(function_def) => {
+            let Suite(body) = &(function_def.1).1;
+            assert!(matches!(body[0], Expr::Defer(Value::FnCall(_))));
+        }
+        _ => panic!("expected a function definition"),
+    }
+    assert!(parse("deferred = 1").is_ok());
+
+    let code = compile::<Rust>("fn f() { defer first() defer second() work() }").unwrap();
+    assert_eq!(code.matches("xasm.try_catch()?;").count(), 2);
+    assert!(code.contains(r#"xasm.push(Value::string("__deferred"));"#));
+
+    // a deferred value runs at the end of the function, so it can't be written in a block within it
+    for script in &[
+        "defer close(a)",
+        "fn f() { while ok { defer close(a) } }",
+        "fn f() { if ok { defer close(a) } }",
+        "fn f() { match x { _ => { defer close(a) } } }",
+        "fn f() { with open(p) as a { defer close(a) } }",
+    ] {
+        assert_eq!(check(&parse(script).unwrap()), Err(Error::DeferOutsideFunction));
+        assert!(compile::<Rust>(script).is_err());
+    }
+    assert_eq!(check(&parse("fn f() { defer close(a) g = fn() { defer close(b) } }").unwrap()), Ok(vec![]));
+    assert!(compile_tests::<Rust>(r#"test "t" { defer close(a) }"#).is_ok());
+}
+
+#[test]
+fn with_test() {
+    assert_eq!(
+        parse("with open(path) as file { file.read() }").unwrap().0[0],
+        Expr::With(
+            value("open(path)"),
+            Identifier("file".to_string()),
+            Suite(vec![Expr::Value(value("file.read()"))])
+        )
+    );
+    assert!(matches!(&parse("with lock as l {}").unwrap().0[0], Expr::With(v, _, _) if *v == name("lock")));
+
+    let code = compile::<Rust>("with open(path) as file { file.read() }").unwrap();
+    assert!(code.contains(r#"xasm.push(Value::string("close"));"#));
+    assert!(code.contains("xasm.try_catch()?;"));
+    assert!(compile::<Golang>("with open(path) as file { file.read() }").unwrap().contains("xasm.TryCatch()"));
+
+
+    // `with` and `as` are only keywords in a `with` block
+    assert_eq!(
+        parse("with = 1\nas = with(as)").unwrap().0,
+        vec![
+            Expr::Assignment(Name::Name(Identifier("with".to_string())), value("1")),
+            Expr::Assignment(Name::Name(Identifier("as".to_string())), value("with(as)")),
+        ]
+    );
+    assert!(matches!(&parse("with with as as {}").unwrap().0[0], Expr::With(v, Identifier(a), _) if *v == name("with") && a == "as"));
+    assert!(parse("without(x)").is_ok());
+}
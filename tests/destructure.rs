@@ -0,0 +1,72 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Destructure, Error, Expr, Function, FunctionDef, Name, Parameter, Rust, Suite};
+
+mod common;
+use common::{ident, name};
+
+fn target(n: &str) -> Destructure {
+    Destructure::Name(Name::Name(ident(n)))
+}
+
+#[test]
+fn destructure_test() {
+    assert_eq!(
+        parse("a, b = b, a").unwrap().0,
+        vec![Expr::Destructure(vec![target("a"), target("b")], vec![name("b"), name("a")])]
+    );
+    assert_eq!(
+        parse("[x, [y, z]] = pair").unwrap().0,
+        vec![Expr::Destructure(
+            vec![Destructure::List(vec![
+                target("x"),
+                Destructure::List(vec![target("y"), target("z")])
+            ])],
+            vec![name("pair")]
+        )]
+    );
+    assert_eq!(
+        parse("{name, age} = person").unwrap().0,
+        vec![Expr::Destructure(
+            vec![Destructure::Dict(vec![ident("name"), ident("age")])],
+            vec![name("person")]
+        )]
+    );
+    assert_eq!(
+        parse("fn f([x, y], {name}) {}").unwrap().0,
+        vec![Expr::FunctionDef(FunctionDef(
+            Name::Name(ident("f")),
+            Function(
+                vec![
+                    Parameter::Pattern(Destructure::List(vec![target("x"), target("y")])),
+                    Parameter::Pattern(Destructure::Dict(vec![ident("name")])),
+                ],
+                Suite(vec![]),
+                None,
+                false
+            ),
+            None
+        ))]
+    );
+
+    // a single target is still a plain assignment
+    assert!(matches!(parse("a = b").unwrap().0[0], Expr::Assignment(_, _)));
+}
+
+#[test]
+fn destructure_compile_test() {
+    // both values are evaluated before either is stored
+    let swap = compile::<Rust>("a, b = b, a").unwrap();
+    let loads = swap.rfind("xasm.load();").unwrap();
+    assert!(loads < swap.find("xasm.store();").unwrap());
+
+    // the right hand side is evaluated once
+    let code = compile::<Rust>("a, self.b = make.pair()").unwrap();
+    assert_eq!(code.matches(r#"Value::string("pair")"#).count(), 1);
+    assert!(code.contains(r#"Value::string("__destructure0")"#));
+    assert!(code.contains("xasm.assign();"));
+
+    assert_eq!(
+        compile::<Rust>("a, b = c, d, e"),
+        Err(format!("{:?}", Error::InvalidDestructure))
+    );
+}
@@ -0,0 +1,70 @@
+extern crate xassembler;
+use xassembler::{compile, Compile, Golang, Rust};
+
+mod common;
+use common::value;
+
+/// The code a single expression compiles to
+fn compiled(script: &str) -> Result<String, xassembler::Error> {
+    Compile::<Rust>::compile(value(script))
+}
+
+#[test]
+fn builtin_test() {
+    // the code lowerings generate calls builtins that the program can't shadow
+    let code = compile::<Rust>("fn add(a, b) { a + b } add(1, 2)").unwrap();
+    assert!(code.contains(r#"xasm.builtin("add")?;"#));
+    assert!(code.contains(r#"xasm.builtin("has")?;"#));
+    let code = compile::<Golang>("list = 1 dict = 2 eq = 3 xs = [1, ...ys] match xs { [1] => 0, _ => { d = {} } }").unwrap();
+    for builtin in &["list", "dict", "eq", "len", "type"] {
+        assert!(code.contains(&format!(r#"xasm.Builtin("{}")"#, builtin)), "{}", builtin);
+    }
+    // while calling a name the program binds still calls the variable
+    assert!(!compile::<Rust>("len(xs)").unwrap().contains(r#"xasm.builtin("len")"#));
+}
+
+#[test]
+fn index_test() {
+    // reading an index calls the `__index__` method of a value that defines it
+    let code = compiled("xs[i]").unwrap();
+    assert!(code.contains(r#"Value::string("__index__")"#));
+    assert!(code.contains("xasm.if_then_else()?;"));
+    assert!(compile::<Golang>("x = xs[0]").unwrap().contains(r#"xasm.Push(NewString("__index__"))"#));
+    // a value and key that aren't names or literals are only evaluated once
+    assert_eq!(
+        compiled("f()[g()]"),
+        compiled("(fn() { __indexed = f() __index_key = g() __indexed[__index_key] })()")
+    );
+    assert_eq!(
+        compiled("xs[0][1]"),
+        compiled("(fn() { __indexed = xs[0] __index_key = 1 __indexed[__index_key] })()")
+    );
+    // a literal never defines the method, and assigning to an index sets the item
+    assert!(!compiled(r#""abc"[0]"#).unwrap().contains("__index__"));
+    assert!(!compile::<Rust>("xs[i] = 1").unwrap().contains("__index__"));
+}
+
+#[test]
+fn call_test() {
+    // calling an object calls its `__call__` method if it defines one
+    let code = compiled("f(x, k = 1)").unwrap();
+    assert!(code.contains(r#"Value::string("__call__")"#));
+    assert!(code.contains("xasm.if_then_else()?;"));
+    // functions, methods and builtins are called directly
+    assert!(!compiled("(fn(a) { a })(1)").unwrap().contains("__call__"));
+    assert!(!compiled("xs.push(1)").unwrap().contains("__call__"));
+    assert!(!compiled("@print(1)").unwrap().contains("__call__"));
+    // any other function is only evaluated once
+    assert_eq!(compiled("f(x)(y)"), compiled("(fn() { __callee = f(x) __callee(y) })()"));
+}
+
+#[test]
+fn str_test() {
+    // converting an object to a string calls its `__str__` method if it defines one
+    let code = compiled("str(x)").unwrap();
+    assert!(code.contains(r#"Value::string("__str__")"#));
+    assert!(!compiled("str(1)").unwrap().contains("__str__"));
+    assert!(!compiled("str(x, y)").unwrap().contains("__str__"));
+    // any other value is only evaluated once
+    assert_eq!(compiled("str(f())"), compiled("(fn() { __converted = f() str(__converted) })()"));
+}
@@ -0,0 +1,57 @@
+extern crate xassembler;
+use xassembler::{docs, format, parse, Doc, Expr, FunctionDef, Printer, StructDef};
+
+fn doc(name: &str, signature: &str, text: &str) -> Doc {
+    Doc { name: name.to_string(), signature: signature.to_string(), text: text.to_string() }
+}
+
+#[test]
+fn doc_comment_test() {
+    let ast = parse("/// Adds two numbers.\n///\n///  Indented.\nfn add(a, b) { a + b }\n/// not a doc\nx = 1\n//// four slashes\nfn f() {}\n/// a blank line\n\nfn g() {}").unwrap();
+    match &ast.0[0] {
+        Expr::FunctionDef(FunctionDef(_, _, doc)) => assert_eq!(doc.as_deref(), Some("Adds two numbers.\n\n Indented.")),
+        _ => panic!("expected a function definition"),
+    }
+    assert!(matches!(&ast.0[2], Expr::FunctionDef(FunctionDef(_, _, None))));
+    assert!(matches!(&ast.0[3], Expr::FunctionDef(FunctionDef(_, _, None))));
+
+    // doc comments don't change what a program means
+    assert_eq!(ast.0[1], parse("x = 1").unwrap().0[0]);
+    match &parse("/// A point\nclass Point {\n    /// Makes one\n    fn new(self) {}\n}").unwrap().0[0] {
+        Expr::StructDef(StructDef(_, methods, doc)) => {
+            assert_eq!(doc.as_deref(), Some("A point"));
+            assert_eq!(methods[0].2.as_deref(), Some("Makes one"));
+        }
+        _ => panic!("expected a class"),
+    }
+
+    // and they are printed before their definitions
+    let script = "/// A point\nclass Point {\n    /// Makes one\n    fn new(self) {}\n}\n";
+    assert_eq!(Printer::default().print(&parse(script).unwrap()), script);
+    let script = "/// Remembers\n@memo\nfn f(n) {\n    n\n}\n";
+    assert_eq!(format(script, &Printer::default()), Ok(script.to_string()));
+}
+
+#[test]
+fn docs_test() {
+    let ast = parse(
+        "/// Adds\nasync fn add(a, b = 1) -> num { a + b }\nfn plain() {}\n/// A point\nclass Point {\n    /// Makes one\n    fn new(self, x) { self.x = x }\n    fn plain(self) {}\n}\nfn outer() {\n    /// Nested\n    fn inner() {}\n}",
+    )
+    .unwrap();
+    assert_eq!(
+        docs(&ast),
+        vec![
+            doc("add", "async fn add(a, b = 1) -> num", "Adds"),
+            doc("Point", "class Point", "A point"),
+            doc("Point.new", "fn new(self, x)", "Makes one"),
+            doc("inner", "fn inner()", "Nested"),
+        ]
+    );
+
+    // the doc comment of a decorated definition comes before its decorators
+    let ast = parse("/// Remembers\n@memo\nfn f(n) { n }\n/// Shapes\n@register\n/// Drawn\nclass Shape {}").unwrap();
+    assert_eq!(
+        docs(&ast),
+        vec![doc("f", "fn f(n)", "Remembers"), doc("Shape", "class Shape", "Shapes\nDrawn")]
+    );
+}
@@ -0,0 +1,97 @@
+extern crate xassembler;
+use xassembler::{check, compile, lower_match, parse, EnumDef, Error, Expr, Golang, Name, Pattern, Rust, Variant};
+
+mod common;
+use common::{ident, with_builtins};
+
+#[test]
+fn enum_test() {
+    let ast = parse(r#"enum Color { Red, Green, Rgb(r, g, b), }"#).unwrap();
+    assert_eq!(
+        ast.0,
+        vec![Expr::EnumDef(EnumDef(
+            Name::Name(ident("Color")),
+            vec![
+                Variant(ident("Red"), vec![]),
+                Variant(ident("Green"), vec![]),
+                Variant(ident("Rgb"), vec![ident("r"), ident("g"), ident("b")]),
+            ]
+        ))]
+    );
+
+    let code = compile::<Rust>(r#"enum Color { Red, Rgb(r, g, b) }"#).unwrap();
+    assert!(code.contains(r#"xasm.push(Value::string("Red"));"#));
+    assert!(code.contains(r#"xasm.push(Value::string("__tag__"));"#));
+    assert!(code.contains(r#"xasm.push(Value::string("__enum__"));"#));
+    assert!(code.contains(r#"xasm.arguments(&["r", "g", "b"], 0, false)?;"#));
+    assert!(compile::<Golang>(r#"enum Color { Red }"#).is_ok());
+
+    // `enum` only starts a declaration when it is a whole word
+    assert!(parse("enumerate(list)").is_ok());
+}
+
+#[test]
+fn variant_pattern_test() {
+    let ast = parse(
+        r#"
+match color {
+    Color.Red => red(),
+    Color.Rgb(r, _, 0) => println(r),
+    _ => other()
+}
+"#,
+    )
+    .unwrap();
+
+    match &ast.0[0] {
+        Expr::Match(_, arms) => {
+            assert!(matches!(arms[0].0[0], Pattern::Value(Name::DotName(_, _))));
+            assert!(matches!(
+                &arms[1].0[0],
+                Pattern::Variant(Name::DotName(_, _), fields) if fields.len() == 3
+            ));
+        }
+        _ => panic!("expected a match"),
+    }
+    assert_eq!(check(&ast), Ok(vec![]));
+
+    let code = compile::<Rust>(r#"match c { Color.Rgb(r, g, b) => println(r), _ => 0 }"#).unwrap();
+    assert!(code.contains(r#"xasm.push(Value::string("Rgb"));"#));
+}
+
+#[test]
+fn variant_lowering_test() {
+    // a variant is matched by its enum as well as its name
+    let lowered = match parse("match c { Color.Rgb(r, _, 0) => f(r) }").unwrap().0.remove(0) {
+        Expr::Match(subject, arms) => lower_match(subject, arms),
+        _ => panic!("expected a match"),
+    };
+    assert_eq!(
+        lowered,
+        with_builtins(
+            r#"
+__match0 = c
+__arm0 = 0
+if eq(__arm0, 0) {
+    if eq(type(__match0), "dict") {
+        if eq(__match0.__tag__, "Rgb") {
+            if eq(__match0.__enum__, Color.__enum__) {
+                if eq(__match0[2], 0) {
+                    r = __match0[0]
+                    __arm0 = 1
+                }
+            }
+        }
+    }
+}
+if eq(__arm0, 1) { f(r) }
+"#,
+            &["eq", "type"]
+        )
+    );
+
+    // a variant can't be named by an index
+    let ast = parse("match c { variants[0](x) => f(x), _ => 0 }").unwrap();
+    assert_eq!(check(&ast), Err(Error::InvalidPattern));
+    assert!(compile::<Rust>("match c { variants[0](x) => f(x), _ => 0 }").is_err());
+}
@@ -0,0 +1,53 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Expr, Golang, Identifier, Rust, Suite};
+
+#[test]
+fn try_catch_test() {
+    match &parse(
+        r#"
+try {
+    risky()
+} catch e {
+    println(e)
+} finally {
+    cleanup()
+}
+"#,
+    )
+    .unwrap()
+    .0[0]
+    {
+        Expr::TryCatch(Suite(body), Some((name, Suite(handler))), Suite(finally)) => {
+            assert_eq!(*name, Identifier("e".to_string()));
+            assert_eq!((body.len(), handler.len(), finally.len()), (1, 1, 1));
+        }
+        _ => panic!("expected a try/catch"),
+    }
+
+    assert!(matches!(
+        parse("try { risky() } finally { cleanup() }").unwrap().0[0],
+        Expr::TryCatch(_, None, _)
+    ));
+    assert!(parse("try { risky() }").is_err());
+
+    // keywords only match whole words
+    assert!(parse("trying = 1 tryhard(trying)").is_ok());
+}
+
+#[test]
+fn throw_test() {
+    let rust = compile::<Rust>(r#"fn f() { throw "oops" }"#).unwrap();
+    assert!(rust.contains("Err(xasm.throw())?;"));
+    // nothing is left unreachable after the throw, such as the function's closing `Ok(())`
+    assert!(!rust.contains("return"));
+    assert!(rust.contains("-> Result<(), Value>"));
+
+    let rust = compile::<Rust>("try { f() } catch e { g(e) }").unwrap();
+    assert!(rust.contains("xasm.call()?;"));
+    assert!(rust.contains("xasm.try_catch()?;"));
+
+    let go = compile::<Golang>(r#"try { throw "oops" } finally { f() }"#).unwrap();
+    assert!(go.contains("xasm.TryCatch()"));
+    // without a handler, the error is thrown again
+    assert_eq!(go.matches("xasm.Throw()").count(), 2);
+}
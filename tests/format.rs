@@ -0,0 +1,93 @@
+extern crate xassembler;
+use xassembler::{format, parse, Printer};
+
+/// Formatting keeps the program the same, and formatting the result again changes nothing
+fn assert_format(source: &str, printer: &Printer, expected: &str) {
+    let formatted = format(source, printer).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(parse(&formatted), parse(source));
+    assert_eq!(format(&formatted, printer).unwrap(), formatted);
+}
+
+#[test]
+fn comment_test() {
+    assert_format(
+        "// header\n\nimport std // trailing\n/* before */ x=1\n\n\nfn f(a) {\n  // inside\n  print(a)\n  // last\n}\n// end\n",
+        &Printer::default(),
+        "// header\n\nimport std // trailing\n/* before */\nx = 1\n\nfn f(a) {\n    // inside\n    print(a)\n    // last\n}\n// end\n",
+    );
+    assert_format(
+        "enum Color { Red, // warm\n Blue }\nclass A {\n// make one\nfn new(self) { self.x = 1 }\n}\n",
+        &Printer::default(),
+        "enum Color {\n    Red, // warm\n    Blue,\n}\nclass A {\n    // make one\n    fn new(self) {\n        self.x = 1\n    }\n}\n",
+    );
+}
+
+#[test]
+fn decorator_test() {
+    assert_format(
+        "@log @cache(10)\nfn f(x) { x }\n@register class A { fn new(self) { self.x = 1 } }",
+        &Printer::default(),
+        "@log\n@cache(10)\nfn f(x) {\n    x\n}\n@register\nclass A {\n    fn new(self) {\n        self.x = 1\n    }\n}\n",
+    );
+}
+
+#[test]
+fn doc_comment_test() {
+    // doc comments stay with their definitions, and the other comments around them are kept
+    assert_format(
+        "// about f\n/// Does nothing\nfn f() {}\n/// before decorators\n@log\n/// between\nfn g() { 1 }\n",
+        &Printer::default(),
+        "// about f\n/// Does nothing\nfn f() {}\n/// before decorators\n/// between\n@log\nfn g() {\n    1\n}\n",
+    );
+}
+
+#[test]
+fn width_test() {
+    let mut printer = Printer::new("\t");
+    printer.width = 20;
+    assert_format(
+        "call(first_argument, second_argument)\nf(a)",
+        &printer,
+        "call(\n\tfirst_argument,\n\tsecond_argument,\n)\nf(a)\n",
+    );
+    assert_format("x = a; [b]", &printer, "x = a;\n[b]\n");
+
+    // the text before a list on its line counts towards the width
+    assert_format("a_long_callee_name(x, y)", &printer, "a_long_callee_name(\n\tx,\n\ty,\n)\n");
+    assert_format("result = func(x, y, z)", &printer, "result = func(\n\tx,\n\ty,\n\tz,\n)\n");
+    // only the outermost list is broken if that is enough
+    assert_format("values = gather([1, 2])", &printer, "values = gather(\n\t[1, 2],\n)\n");
+    let printer = Printer::default();
+    let callee = "a".repeat(95);
+    assert_eq!(printer.print(&parse(&format!("{}(x, y, z)", callee)).unwrap()), format!("{}(\n    x,\n    y,\n    z,\n)\n", callee));
+}
+
+#[test]
+fn expression_comment_test() {
+    // comments inside a list are kept next to their items, which are put on lines of their own
+    assert_format("f(a, // first\n b)", &Printer::default(), "f(\n    a, // first\n    b,\n)\n");
+    assert_format(
+        "x = [1, 2, // two\n 3]",
+        &Printer::default(),
+        "x = [\n    1,\n    2, // two\n    3,\n]\n",
+    );
+    assert_format(
+        "d = {\n // the first\n \"a\": 1, \"b\": g(x, /* why */ y)}",
+        &Printer::default(),
+        "d = {\n    // the first\n    \"a\": 1,\n    \"b\": g(\n        x, /* why */\n        y,\n    ),\n}\n",
+    );
+    assert_format(
+        "fn f(a, // the input\n b) { a }\ng = |x, // one\n y| x",
+        &Printer::default(),
+        "fn f(\n    a, // the input\n    b,\n) {\n    a\n}\ng = |\n    x, // one\n    y,\n| x\n",
+    );
+}
+
+#[test]
+fn check_test() {
+    // formatted source is left as it is
+    let source = "// a comment\nx = [1, 2] // two\n\nwhile x {\n    x = x.pop()\n}\n";
+    assert_eq!(format(source, &Printer::default()).unwrap(), source);
+    assert!(format("if a {", &Printer::default()).is_err());
+}
@@ -0,0 +1,50 @@
+extern crate xassembler;
+use xassembler::{compile, compile_with, parse, Error, Expr, Golang, MemoryLoader, Rust};
+
+#[test]
+fn yield_test() {
+    let ast = parse(
+        r#"
+fn count(n) {
+    i = 0
+    while i.less(n) {
+        yield i
+        i = i.add(1)
+    }
+}
+"#,
+    )
+    .unwrap();
+    match &ast.0[0] {
+        Expr::FunctionDef(function_def) => assert!((function_def.1).1.yields()),
+        _ => panic!("expected a function"),
+    }
+
+    // keywords only match whole words
+    assert!(parse("yielded = 1").is_ok());
+}
+
+#[test]
+fn generator_compile_test() {
+    let rust = compile::<Rust>("fn numbers() { yield 1 yield 2 }").unwrap();
+    assert!(rust.contains("Value::generator("));
+    assert_eq!(rust.matches("xasm.yield_value()?;").count(), 2);
+
+    let go = compile::<Golang>("gen = fn(xs) { while xs { yield xs.pop() } }").unwrap();
+    assert!(go.contains("NewGenerator("));
+    assert!(go.contains("xasm.Yield()"));
+
+    // a yield belongs to the innermost function, so only the inner one is a generator
+    let rust = compile::<Rust>("fn outer() { inner = fn() { yield 1 } inner }").unwrap();
+    assert_eq!(rust.matches("Value::generator(").count(), 1);
+    assert_eq!(rust.matches("Value::function(").count(), 1);
+
+    assert_eq!(compile::<Rust>("yield 1"), Err(format!("{:?}", Error::YieldOutsideFunction)));
+
+    // the top level of a module is outside of every function too, even though it is evaluated in one
+    let loader = MemoryLoader::new().with("numbers", "yield 1");
+    assert_eq!(
+        compile_with::<Rust>(r#"import "numbers""#, &loader),
+        Err(format!("{:?}", Error::YieldOutsideFunction))
+    );
+}
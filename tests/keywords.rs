@@ -0,0 +1,35 @@
+extern crate xassembler;
+use xassembler::{compile, Error, Golang, Rust};
+
+#[test]
+fn keyword_call_test() {
+    let code = compile::<Rust>(r#"f(x, verbose = 1, sep = ", ")"#).unwrap();
+    assert!(code.contains(r#"xasm.keyword_call(&["verbose", "sep"])?;"#));
+    assert!(code.contains("xasm.push(Value::number(1));"));
+
+    let code = compile::<Golang>(r#"obj.log(x, verbose = 1)"#).unwrap();
+    assert!(code.contains(r#"xasm.KeywordMethodCall([]string{"verbose"})"#));
+
+    let code = compile::<Rust>("fn f(x, verbose = 0) {}").unwrap();
+    assert!(code.contains(r#"xasm.arguments(&["x", "verbose"], 1, false)?;"#));
+}
+
+#[test]
+fn keyword_errors_test() {
+    assert_eq!(
+        compile::<Rust>("fn f(x, verbose = 0) {} f(1, quiet = 1)"),
+        Err(format!("{:?}", Error::UnknownKeyword("f".to_string(), "quiet".to_string())))
+    );
+    assert_eq!(
+        compile::<Rust>("f(1, a = 1, a = 2)"),
+        Err(format!("{:?}", Error::DuplicateKeyword("a".to_string())))
+    );
+    assert_eq!(
+        compile::<Rust>("f(a = 1, 2)"),
+        Err(format!("{:?}", Error::InvalidArguments))
+    );
+
+    // a callee that isn't known statically is checked when it runs
+    assert!(compile::<Rust>("f = g f(quiet = 1)").is_ok());
+    assert!(compile::<Rust>("fn f(x) {} fn g(f) { f(quiet = 1) }").is_ok());
+}
@@ -0,0 +1,27 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Argument, Expr, FnCall, Rust, Value};
+
+fn argument(script: &str) -> Value {
+    match &parse(script).unwrap().0[0] {
+        Expr::Value(Value::FnCall(FnCall(_, arguments))) => arguments[0].value().clone(),
+        _ => panic!("expected a call"),
+    }
+}
+
+#[test]
+fn lambda_test() {
+    let function = argument("xs.map(fn(x) { x.double() })");
+    assert_eq!(argument("xs.map(|x| x.double())"), function);
+    assert_eq!(argument("xs.map(fn(x) => x.double())"), function);
+
+    assert!(matches!(argument("f(|| 1)"), Value::Function(_)));
+    assert!(matches!(
+        parse("f(|a, b = 2, ...rest| a)").unwrap().0[0],
+        Expr::Value(Value::FnCall(FnCall(_, ref arguments))) if matches!(arguments[0], Argument::Value(Value::Function(_)))
+    ));
+
+    assert_eq!(
+        compile::<Rust>("g = |x| x.double()").unwrap(),
+        compile::<Rust>("g = fn(x) { x.double() }").unwrap()
+    );
+}
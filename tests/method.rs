@@ -0,0 +1,74 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Argument, Expr, FnCall, Literal, Name, Golang, Rust, Value};
+
+mod common;
+use common::{ident, name, value};
+
+fn call(function: Value, arguments: Vec<Value>) -> Value {
+    Value::FnCall(FnCall(Box::new(function), arguments.into_iter().map(Argument::Value).collect()))
+}
+
+fn dot(head: Value, members: &[&str]) -> Value {
+    Value::Name(Name::DotName(Box::new(head), members.iter().map(|m| ident(m)).collect()))
+}
+
+#[test]
+fn postfix_test() {
+    assert_eq!(value("(make()).run()"), call(dot(call(name("make"), vec![]), &["run"]), vec![]));
+    assert_eq!(
+        value("xs[0].run(1)"),
+        call(
+            dot(
+                Value::Name(Name::IndexName(
+                    Box::new(name("xs")),
+                    vec![Value::Literal(Literal::Number("0".to_string()))]
+                )),
+                &["run"]
+            ),
+            vec![Value::Literal(Literal::Number("1".to_string()))]
+        )
+    );
+    assert_eq!(
+        value("a.b().c.d()"),
+        call(dot(call(dot(name("a"), &["b"]), vec![]), &["c", "d"]), vec![])
+    );
+    assert_eq!(value("f(x)(y)"), call(call(name("f"), vec![name("x")]), vec![name("y")]));
+
+    // function definitions on names are unaffected by calls following them
+    assert!(parse("fn self.run(x) { x }").is_ok());
+}
+
+#[test]
+fn method_call_test() {
+    // the result of a call has its method called, rather than the call being a method call
+    let code = compile::<Rust>("make().run()").unwrap();
+    assert_eq!(code.matches(r#"Value::string("run")"#).count(), 1);
+    assert_eq!(code.matches("xasm.method_call()?;").count(), 2);
+    assert!(compile::<Rust>("make.run()").unwrap().contains(r#"Value::string("run")"#));
+
+    let code = compile::<Rust>("xs.get(0).run().stop()").unwrap();
+    assert_eq!(code.matches("xasm.method_call()?;").count(), 3);
+    assert!(code.contains(r#"Value::string("stop")"#));
+
+    // an error compiling the receiver is returned rather than panicking
+    assert_eq!(compile::<Rust>("(fn(a = 1, b) { a }).x()"), Err("InvalidParameters".to_string()));
+    assert_eq!(compile::<Golang>("(fn(...a, b) { a })[0]"), Err("InvalidParameters".to_string()));
+    assert_eq!(compile::<Rust>("xs[fn(...a, b) { a }]"), Err("InvalidParameters".to_string()));
+}
+
+#[test]
+fn call_in_target_test() {
+    // a call can come anywhere in an assignment target but its end
+    match &parse("make().x = 1").unwrap().0[0] {
+        Expr::Assignment(target, _) => assert_eq!(Value::Name(target.clone()), dot(call(name("make"), vec![]), &["x"])),
+        _ => panic!("expected an assignment"),
+    }
+    match &parse("a.b().c[0] = 1").unwrap().0[0] {
+        Expr::Assignment(Name::IndexName(head, _), _) => {
+            assert_eq!(**head, dot(call(dot(name("a"), &["b"]), vec![]), &["c"]))
+        }
+        _ => panic!("expected an assignment to an index"),
+    }
+    assert!(parse("make() = 1").is_err());
+    assert!(matches!(parse("a.b().c()").unwrap().0[0], Expr::Value(_)));
+}
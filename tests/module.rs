@@ -0,0 +1,133 @@
+extern crate xassembler;
+use xassembler::{
+    compile, compile_with, link, normalize, parse, resolve, Error, Expr, FnCall, Function, Identifier, MemoryLoader, ModuleLoader, Rust, Suite,
+    Value,
+};
+
+#[test]
+fn import_test() {
+    assert_eq!(
+        parse(
+            r#"
+import "lib/math"
+from "lib/math" import add, sub
+from strings import join
+"#
+        )
+        .unwrap()
+        .0,
+        vec![
+            Expr::Import("lib/math".to_string()),
+            Expr::FromImport(
+                "lib/math".to_string(),
+                vec![Identifier("add".to_string()), Identifier("sub".to_string())]
+            ),
+            Expr::FromImport("strings".to_string(), vec![Identifier("join".to_string())]),
+        ]
+    );
+
+    // keywords only match whole words
+    assert_eq!(compile::<Rust>("imports").unwrap(), compile::<Rust>("imports;").unwrap());
+}
+
+#[test]
+fn module_evaluated_once_test() {
+    let loader = MemoryLoader::new()
+        .with("base", "fn double(x) { x.add(x) }")
+        .with("left", r#"import "base" fn left(x) { base.double(x) }"#)
+        .with("right", r#"from base import double right = double"#);
+
+    let code = compile_with::<Rust>(
+        r#"
+import "left"
+import "right"
+left.left(right.right(1))
+"#,
+        &loader,
+    )
+    .unwrap();
+
+    // stored once, and loaded by each of the modules that import it
+    assert_eq!(code.matches("Value::string(\"__module__base\"));\n\txasm.store();").count(), 1);
+    assert_eq!(code.matches("Value::string(\"__module__base\"));\n\txasm.load();").count(), 2);
+    assert!(
+        code.find("\"__module__base\"").unwrap() < code.find("\"__module__left\"").unwrap()
+    );
+}
+
+#[test]
+fn module_path_test() {
+    let loader = MemoryLoader::new()
+        .with("lib/util.xasm", "fn double(x) { x.add(x) }")
+        .with("lib/math", r#"import "./util" from "../lib/./util" import double"#);
+
+    let code = compile_with::<Rust>(
+        r#"
+import "lib/math"
+import "./lib/util.xasm"
+math.double(util.double(1))
+"#,
+        &loader,
+    )
+    .unwrap();
+
+    // every path that resolves to the same module shares its namespace
+    assert_eq!(code.matches("Value::string(\"__module__lib/util\"));\n\txasm.store();").count(), 1);
+    assert_eq!(code.matches("Value::string(\"__module__lib/util\"));\n\txasm.load();").count(), 3);
+    assert_eq!(normalize("./a/../b/c.xasm"), "b/c");
+    assert_eq!(resolve("lib/math", "../std"), "std");
+    assert_eq!(resolve("lib/math", "std"), "std");
+}
+
+#[test]
+fn module_exports_test() {
+    let loader = MemoryLoader::new().with("pair", "first, [second, third] = 1, [2, 3]");
+    let linked = link(parse(r#"from pair import first, third"#).unwrap(), &loader).unwrap();
+    let body = match &linked.0[0] {
+        Expr::Assignment(_, Value::FnCall(FnCall(function, _))) => match &**function {
+            Value::Function(Function(_, Suite(body), _, _)) => body.clone(),
+            _ => panic!("expected the module's namespace function"),
+        },
+        _ => panic!("expected the module to be evaluated first"),
+    };
+    // every name a destructuring assignment at the top level binds is exported
+    for name in &["first", "second", "third"] {
+        assert!(body.contains(&parse(&format!("__module__.{0} = {0}", name)).unwrap().0[0]));
+    }
+
+    // imports are found wherever a function can be written
+    let loader = MemoryLoader::new().with("m", "x = 1");
+    for script in &[r#"(fn() { import "m" m })().x"#, r#"xs[fn() { import "m" m }]"#, r#"(fn() { import "m" m }).x = 1"#] {
+        let linked = link(parse(script).unwrap(), &loader).unwrap();
+        assert_eq!(linked.0.len(), 2, "{}", script);
+    }
+}
+
+#[test]
+fn module_errors_test() {
+    let loader = MemoryLoader::new()
+        .with("a", r#"import "b""#)
+        .with("b", r#"import "c""#)
+        .with("c", r#"import "a""#);
+
+    assert_eq!(
+        compile_with::<Rust>(r#"import "a""#, &loader),
+        Err(format!(
+            "{:?}",
+            Error::CyclicImport(vec!["a".to_string(), "b".to_string(), "c".to_string(), "a".to_string()])
+        ))
+    );
+    assert_eq!(
+        compile::<Rust>(r#"import "missing""#),
+        Err(format!("{:?}", Error::ModuleNotFound("missing".to_string())))
+    );
+    assert_eq!(loader.load("a"), Ok(r#"import "b""#.to_string()));
+
+    // a module's top level names aren't globals, so a module can't declare any
+    let loader = MemoryLoader::new().with("counter", "count = 0\nfn increment() { global count; count = count.add(1) }");
+    assert_eq!(
+        compile_with::<Rust>("import counter", &loader),
+        Err(format!("{:?}", Error::GlobalInModule("counter".to_string())))
+    );
+    assert!(compile::<Rust>("count = 0\nfn increment() { global count; count = count.add(1) }").is_ok());
+}
@@ -0,0 +1,89 @@
+extern crate xassembler;
+use xassembler::{check, parse, Argument, Compile, Error, Expr, FnCall, Function, Golang, Operator, Suite, Type, Value};
+
+mod common;
+use common::{builtin_value, name, number, value, with_builtins};
+
+/// The builtins that operators call
+const BUILTINS: &[&str] = &["has", "eq", "add", "less", "sub", "rem"];
+
+/// The value assigned by a script made of a single assignment
+fn assigned(script: &str) -> Value {
+    match with_builtins(script, BUILTINS).remove(0) {
+        Expr::Assignment(_, value) => value,
+        _ => panic!("expected an assignment"),
+    }
+}
+
+fn operation(left: Value, operator: Operator, right: Value) -> Value {
+    Value::Operation(Box::new(left), operator, Box::new(right))
+}
+
+#[test]
+fn precedence_test() {
+    assert_eq!(
+        value("a + b * c - 1"),
+        operation(
+            operation(name("a"), Operator::Add, operation(name("b"), Operator::Multiply, name("c"))),
+            Operator::Subtract,
+            number("1")
+        )
+    );
+    assert_eq!(
+        value("(a + b) * c <= d"),
+        operation(
+            operation(operation(name("a"), Operator::Add, name("b")), Operator::Multiply, name("c")),
+            Operator::LessEqual,
+            name("d")
+        )
+    );
+    // a sign directly before a number is still part of the literal
+    assert!(matches!(
+        value("f(-1)"),
+        Value::FnCall(FnCall(_, arguments)) if arguments == vec![Argument::Value(number("-1"))]
+    ));
+    assert!(matches!(parse("x = a != b").unwrap().0[0], Expr::Assignment(_, Value::Operation(_, Operator::NotEqual, _))));
+    assert!(matches!(parse("f(a = 1 + 2)").unwrap().0[0], Expr::Value(Value::FnCall(_))));
+}
+
+#[test]
+fn overloading_test() {
+    // an operator calls the method of its left operand if it defines one, and a builtin otherwise
+    assert_eq!(
+        value("a == b").desugar(),
+        assigned(r#"x = if has(a, "__eq__") { a.__eq__(b) } else { eq(a, b) }"#)
+    );
+    // a literal never defines the method
+    assert_eq!(value("1 + a").desugar(), builtin_value("add(1, a)", BUILTINS));
+    assert_eq!(value(r#""a" < 2"#).desugar(), builtin_value(r#"less("a", 2)"#, BUILTINS));
+    // any other operand is only evaluated once
+    let function = match parse("fn() { __left = a.b() __right = c * 2 }").unwrap().0.remove(0) {
+        Expr::Value(Value::Function(Function(parameters, Suite(mut body), returns, asynchronous))) => {
+            body.push(Expr::Value(assigned(
+                r#"x = if has(__left, "__sub__") { __left.__sub__(__right) } else { sub(__left, __right) }"#,
+            )));
+            Function(parameters, Suite(body), returns, asynchronous)
+        }
+        _ => panic!("expected a function"),
+    };
+    assert_eq!(
+        value("a.b() - c * 2").desugar(),
+        Value::FnCall(FnCall(Box::new(Value::Function(function)), vec![]))
+    );
+    assert_eq!(
+        Compile::<Golang>::compile(value("a % b").desugar()),
+        Compile::<Golang>::compile(assigned(r#"x = if has(a, "__mod__") { a.__mod__(b) } else { rem(a, b) }"#))
+    );
+
+    let vector = r#"
+class Vector {
+    fn __add__(other: Vector) -> Vector { self }
+}
+let v: Vector = Vector()
+"#;
+    assert_eq!(check(&parse(&format!("{}let w: Vector = v + v", vector)).unwrap()), Ok(vec![]));
+    assert_eq!(
+        check(&parse(&format!("{}v + 1", vector)).unwrap()),
+        Err(Error::TypeMismatch("other".to_string(), Type::new("Vector"), Type::new("num")))
+    );
+}
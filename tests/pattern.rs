@@ -0,0 +1,113 @@
+extern crate xassembler;
+use xassembler::{
+    check, compile, compile_with_warnings, lower_match, parse, Arm, Expr, Identifier, Literal, MemoryLoader, Name, Pattern, Rust, Warning,
+};
+
+mod common;
+use common::{name, with_builtins};
+
+#[test]
+fn match_test() {
+    let ast = parse(
+        r#"
+match value {
+    1 => one(),
+    "a" | "b" => { letter() }
+    [x, _] => pair(x),
+    Color.Red => red()
+    other => println(other)
+}
+"#,
+    )
+    .unwrap();
+
+    match &ast.0[0] {
+        Expr::Match(subject, arms) => {
+            assert_eq!(*subject, name("value"));
+            assert_eq!(arms.len(), 5);
+            assert_eq!(
+                arms[1].0,
+                vec![
+                    Pattern::Literal(Literal::String("a".to_string())),
+                    Pattern::Literal(Literal::String("b".to_string()))
+                ]
+            );
+            assert_eq!(
+                arms[2].0,
+                vec![Pattern::List(vec![
+                    Pattern::Binding(Identifier("x".to_string())),
+                    Pattern::Wildcard
+                ])]
+            );
+            assert!(matches!(arms[3].0[0], Pattern::Value(Name::DotName(_, _))));
+            assert!(matches!(arms[4], Arm(_, _)));
+        }
+        _ => panic!("expected a match"),
+    }
+    assert_eq!(check(&ast), Ok(vec![]));
+
+    let code = compile::<Rust>(r#"match x { 1 => a(), _ => b() }"#).unwrap();
+    assert!(code.contains(r#"xasm.push(Value::string("__match0"));"#));
+    assert!(code.contains(r#"xasm.builtin("eq")?;"#));
+}
+
+#[test]
+fn non_exhaustive_match_test() {
+    let ast = parse(r#"match x { 1 => a(), [y] => b(y) }"#).unwrap();
+    assert_eq!(check(&ast), Ok(vec![Warning::NonExhaustiveMatch(name("x"))]));
+
+    // compiling reports the warning along with the code
+    let script = r#"fn f(x) { match x { 1 => a() } }"#;
+    let (code, warnings) = compile_with_warnings::<Rust>(script, &MemoryLoader::new()).unwrap();
+    assert_eq!(Ok(code), compile::<Rust>(script));
+    assert_eq!(warnings, vec![Warning::NonExhaustiveMatch(name("x"))]);
+    let script = r#"match x { 1 => a(), _ => b() }"#;
+    assert_eq!(compile_with_warnings::<Rust>(script, &MemoryLoader::new()).unwrap().1, vec![]);
+}
+
+/// The statements a `match` is lowered to
+fn lowered(script: &str) -> Vec<Expr> {
+    match parse(script).unwrap().0.remove(0) {
+        Expr::Match(subject, arms) => lower_match(subject, arms),
+        _ => panic!("expected a match"),
+    }
+}
+
+#[test]
+fn match_lowering_test() {
+    // a list is tested for its type and length before its elements,
+    // and its names are only bound once every element has matched
+    assert_eq!(
+        lowered("match v { [x, 1] => f(x) }"),
+        with_builtins(
+            r#"
+__match0 = v
+__arm0 = 0
+if eq(__arm0, 0) {
+    if eq(type(__match0), "list") {
+        if eq(len(__match0), 2) {
+            if eq(__match0[1], 1) {
+                x = __match0[0]
+                __arm0 = 1
+            }
+        }
+    }
+}
+if eq(__arm0, 1) { f(x) }
+"#,
+            &["eq", "type", "len"]
+        )
+    );
+
+    // a match inside an arm keeps its subject and arm apart from the outer one
+    let outer = lowered("match a { 1 => { match b { _ => c() } }, _ => d() }");
+    assert_eq!(outer[0], parse("__match1 = a").unwrap().0[0]);
+    match &outer[4] {
+        Expr::IfThenElse(_, inner, _) => assert_eq!(
+            inner.0,
+            parse("match b { _ => c() }").unwrap().0
+        ),
+        _ => panic!("expected the bodies of the arms"),
+    }
+    assert_eq!(lowered("match b { _ => c() }")[0], parse("__match0 = b").unwrap().0[0]);
+}
@@ -0,0 +1,123 @@
+extern crate proptest;
+extern crate xassembler;
+use proptest::prelude::*;
+use xassembler::{parse, Printer};
+
+/// Printing a program and parsing it again gives back the same program
+fn assert_round_trip(script: &str) {
+    let ast = parse(script).unwrap();
+    for printer in &[Printer::default(), Printer::new("\t")] {
+        let printed = printer.print(&ast);
+        assert_eq!(parse(&printed), Ok(ast.clone()), "printed as\n{}", printed);
+        assert_eq!(printer.print(&parse(&printed).unwrap()), printed);
+    }
+}
+
+#[test]
+fn print_test() {
+    let ast = parse("fn area(w: num, h: num = 1) -> num { if w { w.mul(h) } else { 0 } }\nx=[1,2]").unwrap();
+    assert_eq!(
+        ast.to_string(),
+        "fn area(w: num, h: num = 1) -> num {\n    if w {\n        w.mul(h)\n    } else {\n        0\n    }\n}\nx = [1, 2]\n"
+    );
+    assert_eq!(Printer::new("\t").print(&parse("while a { b() }").unwrap()), "while a {\n\tb()\n}\n");
+    assert_eq!(parse("f(|x|x+1)").unwrap().0[0].to_string(), "f(|x| x + 1)");
+
+    // values are grouped and separated only where they have to be
+    assert_eq!(parse("(a + b) * (c * d)").unwrap().to_string(), "(a + b) * (c * d)\n");
+    assert_eq!(parse("a - (b - c) - d").unwrap().to_string(), "a - (b - c) - d\n");
+    assert_eq!(parse("x = a; [b]").unwrap().to_string(), "x = a;\n[b]\n");
+    assert_eq!(parse("(if a { b } else { c })").unwrap().to_string(), "(if a { b } else { c })\n");
+}
+
+#[test]
+fn round_trip_test() {
+    for script in &[
+        r#"import std from "lib/util.xasm" import a, b; import "x""#,
+        "let n: num = -1.5 let s = \"a\\\"b\" f = @print",
+        "a.b[c].d = e(1, ...f, g = 2)[1:][::2][a:b:c]",
+        "make().x = 1 a.b()[1:].c[0] = 2",
+        "(f()).x = (g).y[0] (fn() { 1 })()",
+        "xs = [1, ...ys] d = {\"a\": 1, ...e} {} []",
+        "[x.y(z) for [x, {z}] in xs if z for y in x] {k: v for k in ks}",
+        "f = async fn(x) -> num => await (x + 1) g = |a, ...b| |c| a g = fn([a, b], {c}) {}",
+        "class Point { fn new(x, y) { self.x = x } fn norm() -> num { self.x } } class E {}",
+        "enum Shape { Circle(r), Square(side), Empty } enum Never {}",
+        "while a < b { a = a + 1 } if a { b } if a {} else { c }",
+        "try { a() } catch e { b() } finally { c() } try { a } finally {}",
+        "match x { [a, _] | Some(b) => { a } 1 => {}, \"s\" => {{}} a.b => |x| x, }",
+        "[a, [b, c]], {d} = e, f a, b = b, a",
+        "fn f(a) { global g nonlocal h yield a defer h.close() throw a assert a, \"m\" }",
+        "with open(p) as file { file.read() } test \"adds\" { assert 1 + 1 == 2 }",
+        "@memo @trace(1) fn f(n) { n } @register class C {}",
+        "x = if a { b } else if c { d } else { e } (if a { b } else { c }) + 1",
+        "x = 1\n-1\ny = a\n[b]\nz = fn() {}\n(c)",
+    ] {
+        assert_round_trip(script);
+    }
+}
+
+
+fn identifier() -> impl Strategy<Value = String> {
+    prop::sample::select(vec!["a", "b", "foo", "x_1", "test", "asynchronous"]).prop_map(String::from)
+}
+
+fn literal() -> impl Strategy<Value = String> {
+    prop::sample::select(vec!["0", "1", "-2", "3.5", "\"s\"", "\"a b\"", "@ffi"]).prop_map(String::from)
+}
+
+fn value() -> impl Strategy<Value = String> {
+    let operator = prop::sample::select(vec!["==", "!=", "<", "<=", "+", "-", "*", "/", "%"]);
+    prop_oneof![identifier(), literal()].prop_recursive(4, 32, 3, move |inner| {
+        prop_oneof![
+            (inner.clone(), operator.clone(), inner.clone()).prop_map(|(a, op, b)| format!("{} {} {}", a, op, b)),
+            inner.clone().prop_map(|a| format!("({})", a)),
+            (identifier(), identifier()).prop_map(|(a, b)| format!("{}.{}", a, b)),
+            (inner.clone(), identifier()).prop_map(|(a, b)| format!("({}).{}", a, b)),
+            (identifier(), inner.clone(), inner.clone()).prop_map(|(f, a, b)| format!("{}({}, ...{})", f, a, b)),
+            (identifier(), inner.clone()).prop_map(|(a, i)| format!("{}[{}]", a, i)),
+            (identifier(), inner.clone()).prop_map(|(a, i)| format!("{}[{}:]", a, i)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("[{}, ...{}]", a, b)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("{{{}: {}}}", a, b)),
+            inner.clone().prop_map(|a| format!("|x| {}", a)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("fn(x, y = {}) {{ z = {} }}", a, b)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("async fn(x) => await {} + {}", a, b)),
+            (inner.clone(), inner.clone(), inner.clone())
+                .prop_map(|(a, b, c)| format!("if {} {{ {} }} else {{ {} }}", a, b, c)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("[{} for [x, y] in {} if x]", a, b)),
+        ]
+    })
+}
+
+fn statement() -> impl Strategy<Value = String> {
+    let simple = prop_oneof![
+        (identifier(), value()).prop_map(|(a, v)| format!("{} = {}", a, v)),
+        (identifier(), value()).prop_map(|(a, v)| format!("let {}: num = {}", a, v)),
+        value(),
+        (value(), value()).prop_map(|(a, b)| format!("[a, {{b}}], c = {}, {}", a, b)),
+        value().prop_map(|v| format!("throw {}", v)),
+        value().prop_map(|v| format!("defer {}", v)),
+    ];
+    simple.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            (value(), inner.clone()).prop_map(|(v, s)| format!("while {} {{ {} }}", v, s)),
+            (value(), inner.clone(), inner.clone()).prop_map(|(v, a, b)| format!("if {} {{ {} }} else {{ {} }}", v, a, b)),
+            (identifier(), inner.clone()).prop_map(|(f, s)| format!("fn {}(a, ...b) {{ {} }}", f, s)),
+            (value(), value(), inner.clone())
+                .prop_map(|(v, a, s)| format!("match {} {{ [a, _] | 1 => {}, Some(b) => {{ {} }} }}", v, a, s)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("try {{ {} }} catch e {{ {} }}", a, b)),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn round_trip_property(statements in prop::collection::vec(statement(), 1..6)) {
+        let script = statements.join("\n");
+        let ast = parse(&script);
+        prop_assume!(ast.is_ok());
+        let ast = ast.unwrap();
+        let printed = ast.to_string();
+        prop_assert_eq!(parse(&printed), Ok(ast), "printed as\n{}", printed);
+    }
+}
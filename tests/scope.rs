@@ -0,0 +1,43 @@
+extern crate xassembler;
+use xassembler::{check, compile, free_variables, parse, Error, Expr, FunctionDef, Golang, Rust};
+
+mod common;
+use common::ident;
+
+#[test]
+fn declaration_test() {
+    assert_eq!(
+        parse("fn f() { global count, total count = 1 }").unwrap().0[0],
+        parse("fn f() { global count, total; count = 1 }").unwrap().0[0]
+    );
+    match &parse("fn f() { nonlocal n n = 1 }").unwrap().0[0] {
+        Expr::FunctionDef(FunctionDef(_, function, _)) => assert_eq!(function.1 .0[0], Expr::Nonlocal(vec![ident("n")])),
+        _ => panic!("expected a function definition"),
+    }
+    assert!(parse("globals = nonlocality").is_ok());
+
+    let code = compile::<Rust>("fn f() { global count count = 1 }").unwrap();
+    assert!(code.contains(r#"xasm.global(&["count"]);"#));
+    let code = compile::<Golang>("fn f() { n = 0 g = fn() { nonlocal n n = 1 } }").unwrap();
+    assert!(code.contains(r#"xasm.Nonlocal([]string{"n"})"#));
+}
+
+#[test]
+fn resolution_test() {
+    let free = |script: &str| match &parse(script).unwrap().0[0] {
+        Expr::FunctionDef(FunctionDef(_, function, _)) => free_variables(function),
+        _ => panic!("expected a function definition"),
+    };
+    // assigning a nonlocal name updates the captured variable
+    assert!(free("fn f() { nonlocal n n = 1 }").contains(&ident("n")));
+    assert!(!free("fn f() { n = 1 }").contains(&ident("n")));
+    assert!(free("fn f() { if c { nonlocal n } n = 1 }").contains(&ident("n")));
+    // global names are never captured
+    assert!(!free("fn f() { global n n = n }").contains(&ident("n")));
+
+    let check = |script: &str| check(&parse(script).unwrap());
+    assert_eq!(check("fn f() { n = 0 g = fn() { nonlocal n n = 1 } }"), Ok(vec![]));
+    assert_eq!(check("nonlocal n"), Err(Error::NonlocalOutsideFunction));
+    assert_eq!(check("n = 0\nfn f() { nonlocal n }"), Err(Error::UnboundNonlocal("n".to_string())));
+    assert_eq!(check("fn f() { global n n = 1 }\nglobal n"), Ok(vec![]));
+}
@@ -0,0 +1,40 @@
+extern crate xassembler;
+use xassembler::{compile, parse, Expr, Golang, Literal, Name, Rust, Slice, Value};
+
+mod common;
+use common::name;
+
+fn number(n: &str) -> Option<Box<Value>> {
+    Some(Box::new(Value::Literal(Literal::Number(n.to_string()))))
+}
+
+fn sliced(script: &str) -> Value {
+    match &parse(script).unwrap().0[0] {
+        Expr::Value(value) => value.clone(),
+        _ => panic!("expected a value"),
+    }
+}
+
+#[test]
+fn slice_test() {
+    assert_eq!(sliced("xs[1:3]"), Value::Slice(Box::new(name("xs")), Slice(number("1"), number("3"), None)));
+    assert_eq!(sliced("xs[:-1]"), Value::Slice(Box::new(name("xs")), Slice(None, number("-1"), None)));
+    assert_eq!(sliced("xs[2:]"), Value::Slice(Box::new(name("xs")), Slice(number("2"), None, None)));
+    assert_eq!(sliced("xs[::-1]"), Value::Slice(Box::new(name("xs")), Slice(None, None, number("-1"))));
+    assert_eq!(
+        sliced("xs[0][a:b:2]"),
+        Value::Slice(
+            Box::new(Value::Name(Name::IndexName(
+                Box::new(name("xs")),
+                vec![Value::Literal(Literal::Number("0".to_string()))]
+            ))),
+            Slice(Some(Box::new(name("a"))), Some(Box::new(name("b"))), number("2"))
+        )
+    );
+}
+
+#[test]
+fn slice_compile_test() {
+    assert!(compile::<Rust>("ys = xs[1:]").unwrap().contains("xasm.slice(true, false, false)?;"));
+    assert!(compile::<Golang>("f(s[:n:2])").unwrap().contains("xasm.Slice(false, true, true)"));
+}
@@ -0,0 +1,88 @@
+extern crate xassembler;
+use xassembler::{check, compile, compile_tests, compile_tests_with, parse, test_harness, Error, Expr, Golang, Literal, MemoryLoader, Rust, Suite, Value};
+
+mod common;
+use common::{name, with_builtins};
+
+#[test]
+fn assert_test() {
+    assert_eq!(
+        parse(r#"assert ok, "not ok""#).unwrap().0,
+        vec![Expr::Assert(name("ok"), Some(Value::Literal(Literal::String("not ok".to_string()))))]
+    );
+    assert_eq!(parse("assert ok").unwrap().0, vec![Expr::Assert(name("ok"), None)]);
+    assert!(parse("asserted = 1").is_ok());
+
+    let code = compile::<Rust>("assert ok").unwrap();
+    assert!(code.contains(r#"xasm.push(Value::string("assertion failed"));"#));
+    assert!(code.contains("Err(xasm.throw())?;"));
+}
+
+#[test]
+fn test_block_test() {
+    let script = r#"
+fn double(x) { x.mul(2) }
+test "double" {
+    assert eq(double(2), 4), "2 doubled is 4"
+}
+"#;
+    match &parse(script).unwrap().0[1] {
+        Expr::Test(name, Suite(body)) => {
+            assert_eq!(name, "double");
+            assert_eq!(body.len(), 1);
+        }
+        _ => panic!("expected a test"),
+    }
+    // `test` is only a keyword before a string
+    assert!(parse("test = 1; test(test)").is_ok());
+
+    let code = compile::<Rust>(script).unwrap();
+    assert!(!code.contains("2 doubled is 4"));
+
+    let harness = compile_tests::<Rust>(script).unwrap();
+    assert!(harness.contains(r#"xasm.push(Value::string("2 doubled is 4"));"#));
+    assert!(harness.contains(r#"xasm.push(Value::string("test double ... ok"));"#));
+    assert!(harness.contains(r#"xasm.push(Value::string("test double ... FAILED"));"#));
+    assert!(harness.contains("xasm.try_catch()?;"));
+    assert!(compile_tests::<Golang>(script).unwrap().contains("xasm.TryCatch()"));
+}
+
+#[test]
+fn test_summary_test() {
+    let Suite(harness) = test_harness(parse(r#"test "a" { assert ok }"#).unwrap());
+    assert_eq!(harness[0], parse("__failures = 0").unwrap().0[0]);
+    match &harness[1] {
+        Expr::TryCatch(_, Some((_, Suite(handler))), _) => {
+            assert_eq!(handler[1], with_builtins("__failures = add(__failures, 1)", &["add"])[0])
+        }
+        _ => panic!("expected the test to run"),
+    }
+    // the number of failures is reported, and the run fails if there are any
+    assert_eq!(
+        harness[2..],
+        with_builtins(
+            r#"
+println("test result:", sub(1, __failures), "passed;", __failures, "failed")
+if eq(__failures, 0) {} else { throw "tests failed" }
+"#,
+            &["println", "sub", "eq"]
+        )[..]
+    );
+}
+
+#[test]
+fn nested_test_test() {
+    for script in &[
+        r#"if ok { test "a" { assert ok } }"#,
+        r#"fn f() { test "a" { assert ok } }"#,
+        r#"test "a" { test "b" { assert ok } }"#,
+    ] {
+        assert_eq!(check(&parse(script).unwrap()), Err(Error::NestedTest));
+        assert!(compile_tests::<Rust>(script).is_err());
+    }
+
+    // a module can have tests of its own, which aren't run when it is imported
+    let loader = MemoryLoader::new().with("m", r#"x = 1 test "m" { assert eq(x, 1) }"#);
+    let harness = compile_tests_with::<Rust>("import m", &loader).unwrap();
+    assert!(!harness.contains("test m ... ok"));
+}
@@ -0,0 +1,93 @@
+extern crate xassembler;
+use xassembler::{check, compile, parse, Error, Expr, Function, FunctionDef, Literal, Parameter, Rust, Type, Value};
+
+mod common;
+use common::ident;
+
+fn mismatch(name: &str, expected: &str, found: &str) -> Result<Vec<xassembler::Warning>, Error> {
+    Err(Error::TypeMismatch(name.to_string(), Type::new(expected), Type::new(found)))
+}
+
+#[test]
+fn annotation_test() {
+    match &parse("fn area(w: num, h: num = 1, d) -> num { w }").unwrap().0[0] {
+        Expr::FunctionDef(FunctionDef(_, Function(parameters, _, returns, _), _)) => {
+            assert_eq!(
+                parameters[0],
+                Parameter::Typed(Box::new(Parameter::Name(ident("w"))), Type::new("num"))
+            );
+            assert_eq!(
+                parameters[1],
+                Parameter::Typed(
+                    Box::new(Parameter::Default(ident("h"), Value::Literal(Literal::Number("1".to_string())))),
+                    Type::new("num")
+                )
+            );
+            assert_eq!(parameters[2], Parameter::Name(ident("d")));
+            assert_eq!(*returns, Some(Type::new("num")));
+        }
+        _ => panic!("expected a function definition"),
+    }
+
+    assert_eq!(
+        parse(r#"let name: str = "x""#).unwrap().0,
+        vec![Expr::Declaration(
+            ident("name"),
+            Some(Type::new("str")),
+            Value::Literal(Literal::String("x".to_string()))
+        )]
+    );
+    assert!(parse("let f: fn = |x| x").is_ok());
+    assert!(parse("letter = 1").is_ok());
+
+    // annotations don't change the generated code
+    assert_eq!(
+        compile::<Rust>("fn f(a: num) -> num { a }"),
+        compile::<Rust>("fn f(a) { a }")
+    );
+    assert_eq!(compile::<Rust>("let a: num = 1"), compile::<Rust>("a = 1"));
+}
+
+#[test]
+fn type_check_test() {
+    let check = |script: &str| check(&parse(script).unwrap());
+
+    assert_eq!(check(r#"let name: str = 1"#), mismatch("name", "str", "num"));
+    assert_eq!(check(r#"let n: num = 1; n = "one""#), mismatch("n", "num", "str"));
+    assert_eq!(check(r#"let n: any = 1; n = "one""#), Ok(vec![]));
+
+    let area = "fn area(w: num, h: num) -> num { w.mul(h) }\n";
+    assert_eq!(check(&format!("{}area(1, 2)", area)), Ok(vec![]));
+    assert_eq!(check(&format!(r#"{}area(1, "2")"#, area)), mismatch("h", "num", "str"));
+    assert_eq!(check(&format!(r#"{}area(h = "2", w = 1)"#, area)), mismatch("h", "num", "str"));
+    assert_eq!(check(&format!(r#"{}let a: str = area(1, 2)"#, area)), mismatch("a", "str", "num"));
+    assert_eq!(check(r#"fn f() -> num { "f" }"#), mismatch("return", "num", "str"));
+
+    // unannotated values are never reported
+    assert_eq!(check(&format!("{}area(x, y)", area)), Ok(vec![]));
+    assert_eq!(check(&format!("{}area = other\narea(1, \"2\")", area)), Ok(vec![]));
+
+    let shape = r#"
+class Shape {
+    fn scale(factor: num) -> Shape { self }
+}
+let s: Shape = Shape()
+"#;
+    assert_eq!(check(&format!("{}s.scale(2).scale(3)", shape)), Ok(vec![]));
+    assert_eq!(check(&format!(r#"{}s.scale("2")"#, shape)), mismatch("factor", "num", "str"));
+    assert_eq!(check(&format!(r#"{}let n: num = Shape()"#, shape)), mismatch("n", "num", "Shape"));
+
+    // assignments from a function to the names it declares global or nonlocal are checked
+    // against the type of the variable they refer to
+    assert_eq!(check(r#"let n: num = 1; fn f() { global n; n = "one" }"#), mismatch("n", "num", "str"));
+    assert_eq!(
+        check(r#"fn f() { let n: num = 1; fn g() { nonlocal n; n = "one" } }"#),
+        mismatch("n", "num", "str")
+    );
+    assert_eq!(
+        check(r#"let n: num = 1; fn f() { let n: str = ""; fn g() { global n; n = "one" } }"#),
+        mismatch("n", "num", "str")
+    );
+    // without a declaration, the function binds a variable of its own
+    assert_eq!(check(r#"let n: num = 1; fn f() { n = "one" }"#), Ok(vec![]));
+}
@@ -0,0 +1,63 @@
+extern crate xassembler;
+use xassembler::visit::{self, Fold, Visit, VisitMut};
+use xassembler::{parse, FnCall, Identifier, Literal, Name, Value};
+
+/// Counts the calls in a program, including those inside functions and comprehensions
+#[derive(Default)]
+struct Calls(usize);
+
+impl Visit for Calls {
+    fn visit_fn_call(&mut self, call: &FnCall) {
+        self.0 += 1;
+        visit::visit_fn_call(self, call)
+    }
+}
+
+/// Renames every occurrence of a name
+struct Rename(&'static str, &'static str);
+
+impl VisitMut for Rename {
+    fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+        if identifier.0 == self.0 {
+            identifier.0 = self.1.to_string();
+        }
+    }
+}
+
+/// Replaces `DEBUG` with a literal number
+struct Constant;
+
+impl Fold for Constant {
+    fn fold_value(&mut self, value: Value) -> Value {
+        match value {
+            Value::Name(Name::Name(Identifier(ref name))) if name == "DEBUG" => {
+                Value::Literal(Literal::Number("0".to_string()))
+            }
+            value => visit::fold_value(self, value),
+        }
+    }
+}
+
+#[test]
+fn visit_test() {
+    let mut calls = Calls::default();
+    calls.visit_suite(&parse("fn f(x = g()) { [h(y) for y in k(x)] } if a.b(c) { d[e()] = 1 }").unwrap());
+    assert_eq!(calls.0, 5);
+
+    let mut ast = parse("fn old(a) { old(a.old) } match x { Old(n) => old(n) }").unwrap();
+    Rename("old", "new").visit_suite_mut(&mut ast);
+    assert_eq!(ast, parse("fn new(a) { new(a.new) } match x { Old(n) => new(n) }").unwrap());
+}
+
+#[test]
+fn fold_test() {
+    let ast = Constant.fold_suite(parse("if DEBUG { log(DEBUG, [DEBUG]) } f = |x| DEBUG").unwrap());
+    assert_eq!(ast, parse("if 0 { log(0, [0]) } f = |x| 0").unwrap());
+
+    // a fold that changes nothing rebuilds the same program
+    struct Identity;
+    impl Fold for Identity {}
+    let script = "async fn f(a: num, ...b) -> num { with g() as h { defer h.i() await j[1:2] } }";
+    let ast = parse(script).unwrap();
+    assert_eq!(Identity.fold_suite(ast.clone()), ast);
+}